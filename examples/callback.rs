@@ -0,0 +1,36 @@
+use alpheidae::*;
+use serde::Deserialize;
+use std::time::Duration;
+
+#[derive(Deserialize)]
+struct Config {
+    consumer_key: String,
+    consumer_secret: String,
+}
+
+#[actix_rt::main]
+async fn main() {
+    let config: Config = envy::from_env().unwrap();
+    let consumer_keys = KeyPair::new(config.consumer_key, config.consumer_secret);
+
+    // http://127.0.0.1:8080/callback must be registered as a callback URL.
+    let listener = oauth::callback_server()
+        .timeout(Duration::from_secs(300))
+        .bind("127.0.0.1:8080")
+        .unwrap();
+
+    let request_token = oauth::request_token(&consumer_keys, listener.callback_url().to_string())
+        .send()
+        .await
+        .unwrap();
+    assert!(request_token.oauth_callback_confirmed);
+    listener.expect(&request_token);
+
+    println!("Please open: {}", request_token.get_redirect_url());
+    let response = listener
+        .access_token(&consumer_keys, &request_token)
+        .await
+        .unwrap();
+
+    println!("Hello, {}!", response.screen_name);
+}
@@ -49,10 +49,7 @@ async fn main() {
 
     println!("Hello, {}!", response.screen_name);
 
-    let tokens = TokenKeys::new(consumer_keys).oauth_tokens(KeyPair::new(
-        response.oauth_token,
-        response.oauth_token_secret,
-    ));
+    let tokens = response.into_token_keys(consumer_keys);
 
     let tweet = read_tweet();
     statuses::update(&tokens, tweet).send().await.unwrap();
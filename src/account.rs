@@ -0,0 +1,106 @@
+use crate::error::*;
+use crate::oauth::AccessLevel;
+use crate::utils::*;
+use crate::*;
+
+pub fn verify_credentials<'a>(auth: impl Into<Auth<'a>>) -> VerifyCredentials<'a> {
+    VerifyCredentials::new(auth)
+}
+
+/// `GET account/verify_credentials`
+pub struct VerifyCredentials<'a> {
+    auth: Auth<'a>,
+    include_entities: Option<bool>,
+    skip_status: Option<bool>,
+    include_email: Option<bool>,
+}
+
+impl<'a> VerifyCredentials<'a> {
+    pub fn new(auth: impl Into<Auth<'a>>) -> Self {
+        Self {
+            auth: auth.into(),
+            include_entities: None,
+            skip_status: None,
+            include_email: None,
+        }
+    }
+
+    pub fn include_entities(mut self, include_entities: bool) -> Self {
+        self.include_entities = Some(include_entities);
+        self
+    }
+
+    pub fn skip_status(mut self, skip_status: bool) -> Self {
+        self.skip_status = Some(skip_status);
+        self
+    }
+
+    /// Requires the "Request email addresses from users" permission.
+    pub fn include_email(mut self, include_email: bool) -> Self {
+        self.include_email = Some(include_email);
+        self
+    }
+
+    pub async fn send(self) -> Result<User> {
+        Ok(self.send_with_access_level().await?.0)
+    }
+
+    /// Also returns the `x-access-level` of the tokens.
+    pub async fn send_with_access_level(self) -> Result<(User, Option<AccessLevel>)> {
+        self.send_to("https://api.twitter.com/1.1/account/verify_credentials.json")
+            .await
+    }
+
+    async fn send_to(self, url: &str) -> Result<(User, Option<AccessLevel>)> {
+        self.auth
+            .check("account/verify_credentials", &[AuthKind::User])?;
+        let mut request = Request::get(url);
+
+        _opt_query!(self, request, include_entities);
+        _opt_query!(self, request, skip_status);
+        _opt_query!(self, request, include_email);
+
+        let mut res = request.send_checked(self.auth).await?;
+        let access_level = res
+            .headers()
+            .get("x-access-level")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        Ok((res.json().await?, access_level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNAUTHORIZED: &str = r#"{"errors":[{"code":89,"message":"Invalid or expired token."}]}"#;
+
+    #[actix_rt::test]
+    async fn unauthorized() {
+        let srv = actix_web::test::start(|| {
+            actix_web::App::new().route(
+                "/verify_credentials.json",
+                actix_web::web::get().to(|| {
+                    std::future::ready(
+                        actix_web::HttpResponse::Unauthorized()
+                            .content_type("application/json")
+                            .body(UNAUTHORIZED),
+                    )
+                }),
+            )
+        });
+        let tokens = TokenKeys::new(KeyPair::new("ck".to_string(), "cs".to_string()))
+            .oauth_tokens(KeyPair::new("at".to_string(), "as".to_string()));
+        let result = verify_credentials(&tokens)
+            .send_to(&srv.url("/verify_credentials.json"))
+            .await;
+        match result {
+            Err(Error::TwitterError(error)) => {
+                assert_eq!(error.status, 401);
+                assert!(error.has_code(TwitterError::INVALID_OR_EXPIRED_TOKEN));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
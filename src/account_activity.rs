@@ -0,0 +1,732 @@
+//! Account Activity API webhooks, subscriptions and events
+//!
+//! https://developer.twitter.com/en/docs/twitter-api/enterprise/account-activity-api/guides/securing-webhooks
+
+use crate::error::*;
+use crate::utils::*;
+use crate::*;
+use actix_web::dev::Payload;
+use actix_web::error::{ErrorInternalServerError, ErrorUnauthorized};
+use actix_web::web::{self, Bytes};
+use actix_web::{FromRequest, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use serde::de::{self, Deserializer};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+fn hmac_sha256(key: &str, data: &[u8]) -> Vec<u8> {
+    let key = PKey::hmac(key.as_bytes()).expect("HMAC key");
+    let mut signer = Signer::new(MessageDigest::sha256(), &key).expect("HMAC-SHA256 signer");
+    signer.update(data).expect("HMAC-SHA256 update");
+    signer.sign_to_vec().expect("HMAC-SHA256 signature")
+}
+
+/// `sha256=<base64>` of `data` keyed with the consumer secret.
+fn signature(consumer_keys: &KeyPair, data: &[u8]) -> String {
+    format!(
+        "sha256={}",
+        base64::encode(hmac_sha256(&consumer_keys.secret, data))
+    )
+}
+
+/// The `response_token` answering a challenge-response check.
+pub fn crc_response_token(consumer_keys: &KeyPair, crc_token: &str) -> String {
+    signature(consumer_keys, crc_token.as_bytes())
+}
+
+/// Checks the `x-twitter-webhooks-signature` header of an incoming event.
+pub fn validate_signature(consumer_keys: &KeyPair, signature_header: &str, body: &[u8]) -> bool {
+    let expected = signature(consumer_keys, body);
+    expected.len() == signature_header.len()
+        && openssl::memcmp::eq(expected.as_bytes(), signature_header.as_bytes())
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CrcQuery {
+    pub crc_token: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CrcResponse {
+    pub response_token: String,
+}
+
+/// Answers `GET` challenge-response checks.
+///
+/// Requires the consumer keys as `web::Data<KeyPair>`:
+///
+/// ```no_run
+/// # use actix_web::{web, App};
+/// # use alpheidae::*;
+/// # let consumer_keys = KeyPair::new(String::new(), String::new());
+/// App::new()
+///     .app_data(web::Data::new(consumer_keys))
+///     .route("/webhook", web::get().to(account_activity::crc_handler));
+/// ```
+pub async fn crc_handler(
+    consumer_keys: web::Data<KeyPair>,
+    query: web::Query<CrcQuery>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(CrcResponse {
+        response_token: crc_response_token(&consumer_keys, &query.crc_token),
+    })
+}
+
+/// The body of a webhook `POST` whose signature has been validated against
+/// the consumer keys in `web::Data<KeyPair>`. Responds 401 otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedPayload(pub Bytes);
+
+impl FromRequest for SignedPayload {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = std::result::Result<Self, Self::Error>>>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let consumer_keys = req.app_data::<web::Data<KeyPair>>().cloned();
+        let signature = req
+            .headers()
+            .get("x-twitter-webhooks-signature")
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+        let body = Bytes::from_request(req, payload);
+        Box::pin(async move {
+            let consumer_keys = consumer_keys
+                .ok_or_else(|| ErrorInternalServerError("consumer keys are not configured"))?;
+            let signature = signature
+                .ok_or_else(|| ErrorUnauthorized("missing x-twitter-webhooks-signature"))?;
+            let body = body.await?;
+            if validate_signature(&consumer_keys, &signature, &body) {
+                Ok(SignedPayload(body))
+            } else {
+                Err(ErrorUnauthorized("invalid x-twitter-webhooks-signature"))
+            }
+        })
+    }
+}
+
+impl SignedPayload {
+    pub fn activity(&self) -> serde_json::Result<AccountActivity> {
+        serde_json::from_slice(&self.0)
+    }
+}
+
+const BASE_URL: &str = "https://api.twitter.com/1.1/account_activity/all";
+
+/// Registers `url` as the webhook of `env_name`. Twitter sends a CRC check to
+/// `url` before answering.
+pub fn register_webhook<'a>(
+    auth: impl Into<Auth<'a>>,
+    env_name: String,
+    url: String,
+) -> RegisterWebhook<'a> {
+    RegisterWebhook {
+        auth: auth.into(),
+        env_name,
+        url,
+    }
+}
+
+pub struct RegisterWebhook<'a> {
+    auth: Auth<'a>,
+    env_name: String,
+    url: String,
+}
+
+impl<'a> RegisterWebhook<'a> {
+    pub async fn send(self) -> Result<Webhook> {
+        self.auth
+            .check("account_activity/all/:env_name/webhooks", &[AuthKind::User])?;
+        let url = format!("{}/{}/webhooks.json", BASE_URL, self.env_name);
+        let mut request = Request::post(url);
+        request.query("url", self.url);
+        request.send_json(self.auth).await
+    }
+}
+
+pub fn webhooks<'a>(auth: impl Into<Auth<'a>>, env_name: String) -> Webhooks<'a> {
+    Webhooks {
+        auth: auth.into(),
+        env_name,
+    }
+}
+
+pub struct Webhooks<'a> {
+    auth: Auth<'a>,
+    env_name: String,
+}
+
+impl<'a> Webhooks<'a> {
+    pub async fn send(self) -> Result<Vec<Webhook>> {
+        self.auth.check(
+            "account_activity/all/:env_name/webhooks",
+            &[AuthKind::AppOnly],
+        )?;
+        let url = format!("{}/{}/webhooks.json", BASE_URL, self.env_name);
+        Request::get(url).send_json(self.auth).await
+    }
+}
+
+/// Triggers a CRC check, re-enabling a webhook marked invalid.
+pub fn trigger_crc<'a>(
+    auth: impl Into<Auth<'a>>,
+    env_name: String,
+    webhook_id: String,
+) -> TriggerCrc<'a> {
+    TriggerCrc {
+        auth: auth.into(),
+        env_name,
+        webhook_id,
+    }
+}
+
+pub struct TriggerCrc<'a> {
+    auth: Auth<'a>,
+    env_name: String,
+    webhook_id: String,
+}
+
+impl<'a> TriggerCrc<'a> {
+    pub async fn send(self) -> Result<()> {
+        self.auth.check(
+            "account_activity/all/:env_name/webhooks/:webhook_id",
+            &[AuthKind::User, AuthKind::AppOnly],
+        )?;
+        let url = format!(
+            "{}/{}/webhooks/{}.json",
+            BASE_URL, self.env_name, self.webhook_id
+        );
+        Request::put(url).send_checked(self.auth).await?;
+        Ok(())
+    }
+}
+
+pub fn delete_webhook<'a>(
+    auth: impl Into<Auth<'a>>,
+    env_name: String,
+    webhook_id: String,
+) -> DeleteWebhook<'a> {
+    DeleteWebhook {
+        auth: auth.into(),
+        env_name,
+        webhook_id,
+    }
+}
+
+pub struct DeleteWebhook<'a> {
+    auth: Auth<'a>,
+    env_name: String,
+    webhook_id: String,
+}
+
+impl<'a> DeleteWebhook<'a> {
+    pub async fn send(self) -> Result<()> {
+        self.auth.check(
+            "account_activity/all/:env_name/webhooks/:webhook_id",
+            &[AuthKind::User, AuthKind::AppOnly],
+        )?;
+        let url = format!(
+            "{}/{}/webhooks/{}.json",
+            BASE_URL, self.env_name, self.webhook_id
+        );
+        Request::delete(url).send_checked(self.auth).await?;
+        Ok(())
+    }
+}
+
+/// Subscribes the authenticating user to `env_name`.
+pub fn subscribe<'a>(auth: impl Into<Auth<'a>>, env_name: String) -> Subscribe<'a> {
+    Subscribe {
+        auth: auth.into(),
+        env_name,
+    }
+}
+
+pub struct Subscribe<'a> {
+    auth: Auth<'a>,
+    env_name: String,
+}
+
+impl<'a> Subscribe<'a> {
+    pub async fn send(self) -> Result<()> {
+        self.auth.check(
+            "account_activity/all/:env_name/subscriptions",
+            &[AuthKind::User],
+        )?;
+        let url = format!("{}/{}/subscriptions.json", BASE_URL, self.env_name);
+        Request::post(url).send_checked(self.auth).await?;
+        Ok(())
+    }
+}
+
+/// Whether the authenticating user is subscribed to `env_name`.
+pub fn subscription<'a>(auth: impl Into<Auth<'a>>, env_name: String) -> Subscription<'a> {
+    Subscription {
+        auth: auth.into(),
+        env_name,
+    }
+}
+
+pub struct Subscription<'a> {
+    auth: Auth<'a>,
+    env_name: String,
+}
+
+impl<'a> Subscription<'a> {
+    pub async fn send(self) -> Result<bool> {
+        self.auth.check(
+            "account_activity/all/:env_name/subscriptions",
+            &[AuthKind::User],
+        )?;
+        let url = format!("{}/{}/subscriptions.json", BASE_URL, self.env_name);
+        match Request::get(url).send_checked(self.auth).await {
+            Ok(_) => Ok(true),
+            Err(Error::TwitterError(error)) if error.has_code(TwitterError::PAGE_NOT_FOUND) => {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Removes the subscription of `user_id` from `env_name`.
+pub fn unsubscribe<'a>(
+    auth: impl Into<Auth<'a>>,
+    env_name: String,
+    user_id: impl Into<UserId>,
+) -> Unsubscribe<'a> {
+    Unsubscribe {
+        auth: auth.into(),
+        env_name,
+        user_id: user_id.into(),
+    }
+}
+
+pub struct Unsubscribe<'a> {
+    auth: Auth<'a>,
+    env_name: String,
+    user_id: UserId,
+}
+
+impl<'a> Unsubscribe<'a> {
+    pub async fn send(self) -> Result<()> {
+        self.auth.check(
+            "account_activity/all/:env_name/subscriptions/:user_id",
+            &[AuthKind::AppOnly],
+        )?;
+        let url = format!(
+            "{}/{}/subscriptions/{}.json",
+            BASE_URL, self.env_name, self.user_id
+        );
+        Request::delete(url).send_checked(self.auth).await?;
+        Ok(())
+    }
+}
+
+pub fn subscriptions<'a>(auth: impl Into<Auth<'a>>, env_name: String) -> Subscriptions<'a> {
+    Subscriptions {
+        auth: auth.into(),
+        env_name,
+    }
+}
+
+pub struct Subscriptions<'a> {
+    auth: Auth<'a>,
+    env_name: String,
+}
+
+impl<'a> Subscriptions<'a> {
+    pub async fn send(self) -> Result<SubscriptionList> {
+        self.auth.check(
+            "account_activity/all/:env_name/subscriptions/list",
+            &[AuthKind::AppOnly],
+        )?;
+        let url = format!("{}/{}/subscriptions/list.json", BASE_URL, self.env_name);
+        Request::get(url).send_json(self.auth).await
+    }
+}
+
+pub fn subscriptions_count<'a>(auth: impl Into<Auth<'a>>) -> SubscriptionsCount<'a> {
+    SubscriptionsCount { auth: auth.into() }
+}
+
+pub struct SubscriptionsCount<'a> {
+    auth: Auth<'a>,
+}
+
+impl<'a> SubscriptionsCount<'a> {
+    pub async fn send(self) -> Result<SubscriptionCount> {
+        self.auth.check(
+            "account_activity/all/subscriptions/count",
+            &[AuthKind::AppOnly],
+        )?;
+        let url = format!("{}/subscriptions/count.json", BASE_URL);
+        Request::get(url).send_json(self.auth).await
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub valid: bool,
+    pub created_timestamp: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SubscriptionList {
+    pub environment: String,
+    pub application_id: String,
+    pub subscriptions: Vec<Subscriber>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Subscriber {
+    pub user_id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SubscriptionCount {
+    pub account_name: String,
+    pub subscriptions_count_all: String,
+    pub subscriptions_count_direct_messages: String,
+}
+
+/// The JSON body of a webhook `POST`.
+///
+/// ```no_run
+/// # use alpheidae::account_activity::*;
+/// async fn events(payload: SignedPayload) -> actix_web::HttpResponse {
+///     match payload.activity() {
+///         Ok(activity) => {
+///             if let AccountActivityEvent::TweetCreateEvents(tweets) = activity.event {
+///                 // ...
+///             }
+///             actix_web::HttpResponse::Ok().finish()
+///         }
+///         Err(_) => actix_web::HttpResponse::BadRequest().finish(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AccountActivity {
+    pub for_user_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_has_blocked: Option<bool>,
+    /// Users referenced by `direct_message_events`, by id.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub users: HashMap<String, MessageUser>,
+    /// Apps referenced by `direct_message_events`, by id.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub apps: HashMap<String, App>,
+    #[serde(flatten)]
+    pub event: AccountActivityEvent,
+}
+
+/// An enum keyed by a single field of an object, like serde's externally
+/// tagged enums, but falling back to `Unknown` with the object as sent.
+macro_rules! keyed_enum {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_attr:meta])* $variant:ident($ty:ty) = $key:literal,)*
+        }
+    ) => {
+        $(#[$attr])*
+        pub enum $name {
+            $($(#[$variant_attr])* $variant($ty),)*
+            /// Not modeled by this crate yet, with all fields as sent.
+            Unknown(serde_json::Map<String, serde_json::Value>),
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                match self {
+                    $(Self::$variant(value) => {
+                        let mut map = serializer.serialize_map(Some(1))?;
+                        map.serialize_entry($key, value)?;
+                        map.end()
+                    })*
+                    Self::Unknown(fields) => fields.serialize(serializer),
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+                let mut fields = serde_json::Map::deserialize(deserializer)?;
+                $(if let Some(value) = fields.remove($key) {
+                    return serde_json::from_value(value)
+                        .map(Self::$variant)
+                        .map_err(de::Error::custom);
+                })*
+                Ok(Self::Unknown(fields))
+            }
+        }
+    };
+}
+
+keyed_enum! {
+    #[derive(Debug)]
+    pub enum AccountActivityEvent {
+        TweetCreateEvents(Vec<Tweet>) = "tweet_create_events",
+        FavoriteEvents(Vec<FavoriteEvent>) = "favorite_events",
+        FollowEvents(Vec<RelationshipEvent>) = "follow_events",
+        BlockEvents(Vec<RelationshipEvent>) = "block_events",
+        MuteEvents(Vec<RelationshipEvent>) = "mute_events",
+        DirectMessageEvents(Vec<DirectMessageEvent>) = "direct_message_events",
+        DirectMessageIndicateTypingEvents(Vec<TypingEvent>) =
+            "direct_message_indicate_typing_events",
+        DirectMessageMarkReadEvents(Vec<MarkReadEvent>) = "direct_message_mark_read_events",
+        TweetDeleteEvents(Vec<TweetDeleteEvent>) = "tweet_delete_events",
+        UserEvent(UserEvent) = "user_event",
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FavoriteEvent {
+    pub id: String,
+    #[serde(with = "crate::datetime::created_at")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::datetime::timestamp_ms::number")]
+    pub timestamp_ms: DateTime<Utc>,
+    pub favorited_status: Tweet,
+    pub user: User,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// A follow, block or mute, or its undoing.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RelationshipEvent {
+    /// e.g. `follow` or `unfollow`
+    pub r#type: String,
+    #[serde(with = "crate::datetime::timestamp_ms")]
+    pub created_timestamp: DateTime<Utc>,
+    pub target: User,
+    pub source: User,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct DirectMessageEvent {
+    pub r#type: String,
+    pub id: String,
+    #[serde(with = "crate::datetime::timestamp_ms")]
+    pub created_timestamp: DateTime<Utc>,
+    pub message_create: MessageCreate,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct MessageCreate {
+    pub target: Recipient,
+    pub sender_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_app_id: Option<String>,
+    pub message_data: MessageData,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Recipient {
+    pub recipient_id: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct MessageData {
+    pub text: String,
+    /// e.g. `entities`, `attachment` or `quick_reply_response`.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct TypingEvent {
+    #[serde(with = "crate::datetime::timestamp_ms")]
+    pub created_timestamp: DateTime<Utc>,
+    pub sender_id: String,
+    pub target: Recipient,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct MarkReadEvent {
+    #[serde(with = "crate::datetime::timestamp_ms")]
+    pub created_timestamp: DateTime<Utc>,
+    pub sender_id: String,
+    pub target: Recipient,
+    pub last_read_event_id: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct TweetDeleteEvent {
+    pub status: DeletedStatus,
+    #[serde(with = "crate::datetime::timestamp_ms")]
+    pub timestamp_ms: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct DeletedStatus {
+    pub id: String,
+    pub user_id: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+keyed_enum! {
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum UserEvent {
+        /// The user revoked the app's access.
+        Revoke(Revoke) = "revoke",
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Revoke {
+    pub date_time: String,
+    pub target: RevokeTarget,
+    pub source: RevokeSource,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RevokeTarget {
+    pub app_id: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RevokeSource {
+    pub user_id: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// The abridged user objects accompanying `direct_message_events`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct MessageUser {
+    pub id: String,
+    #[serde(with = "crate::datetime::timestamp_ms")]
+    pub created_timestamp: DateTime<Utc>,
+    pub name: String,
+    pub screen_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub protected: bool,
+    pub verified: bool,
+    pub followers_count: u64,
+    pub friends_count: u64,
+    pub statuses_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_image_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_image_url_https: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct App {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+
+    fn consumer_keys() -> KeyPair {
+        KeyPair::new(
+            "xvz1evFS4wEEPTGEFPHBog".to_string(),
+            "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw".to_string(),
+        )
+    }
+
+    const BODY: &str = r#"{"for_user_id":"2244994945","tweet_delete_events":[]}"#;
+    const BODY_SIGNATURE: &str = "sha256=+fJqtGRuiBDFMD0v5+twsbPy7epKD/0X05FCx0M3XTU=";
+
+    #[test]
+    fn response_token() {
+        assert_eq!(
+            crc_response_token(&consumer_keys(), "9b4507b3-9040-4669-9ba1-a5c1f7a3c5d8"),
+            "sha256=d2gUM+cF8nErh19Gl/KhtW3quo/TDixyM32TVwM3OSg="
+        );
+    }
+
+    #[test]
+    fn signature() {
+        let keys = consumer_keys();
+        assert!(validate_signature(&keys, BODY_SIGNATURE, BODY.as_bytes()));
+        assert!(!validate_signature(&keys, BODY_SIGNATURE, b"{}"));
+        assert!(!validate_signature(&keys, "sha256=", BODY.as_bytes()));
+    }
+
+    async fn events(payload: SignedPayload) -> HttpResponse {
+        HttpResponse::Ok().body(payload.0)
+    }
+
+    #[actix_rt::test]
+    async fn handlers() {
+        let mut app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(consumer_keys()))
+                .route("/webhook", web::get().to(crc_handler))
+                .route("/webhook", web::post().to(events)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/webhook?crc_token=9b4507b3-9040-4669-9ba1-a5c1f7a3c5d8")
+            .to_request();
+        let res: CrcResponse = test::read_response_json(&mut app, req).await;
+        assert_eq!(
+            res.response_token,
+            "sha256=d2gUM+cF8nErh19Gl/KhtW3quo/TDixyM32TVwM3OSg="
+        );
+
+        let req = test::TestRequest::post()
+            .uri("/webhook")
+            .header("x-twitter-webhooks-signature", BODY_SIGNATURE)
+            .set_payload(BODY)
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        assert!(res.status().is_success());
+        assert_eq!(test::read_body(res).await, BODY);
+
+        let req = test::TestRequest::post()
+            .uri("/webhook")
+            .header("x-twitter-webhooks-signature", BODY_SIGNATURE)
+            .set_payload("{}")
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        assert_eq!(res.status(), 401);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::error::*;
+use crate::*;
+use std::fmt;
+
+/// Credentials an endpoint is called with.
+#[derive(Clone, Copy, Debug)]
+pub enum Auth<'a> {
+    /// OAuth 1.0a user context.
+    User(&'a TokenKeys),
+    /// OAuth 1.0a signed with the consumer keys only, e.g. for `oauth/request_token`.
+    Consumer(&'a KeyPair),
+    /// OAuth 2.0 application-only bearer token.
+    AppOnly(&'a BearerToken),
+    /// OAuth 2.0 user access token.
+    OAuth2User(&'a OAuth2Token),
+}
+
+impl<'a> Auth<'a> {
+    pub fn kind(&self) -> AuthKind {
+        match self {
+            Self::User(tokens) if tokens.oauth_tokens.is_none() => AuthKind::Consumer,
+            Self::User(_) => AuthKind::User,
+            Self::Consumer(_) => AuthKind::Consumer,
+            Self::AppOnly(_) => AuthKind::AppOnly,
+            Self::OAuth2User(_) => AuthKind::OAuth2User,
+        }
+    }
+
+    pub(crate) fn check(&self, endpoint: &'static str, accepted: &[AuthKind]) -> Result<()> {
+        let kind = self.kind();
+        if accepted.contains(&kind) {
+            Ok(())
+        } else {
+            Err(UnsupportedAuth { endpoint, kind }.into())
+        }
+    }
+}
+
+impl<'a> From<&'a TokenKeys> for Auth<'a> {
+    fn from(tokens: &'a TokenKeys) -> Self {
+        Self::User(tokens)
+    }
+}
+
+impl<'a> From<&'a KeyPair> for Auth<'a> {
+    fn from(keys: &'a KeyPair) -> Self {
+        Self::Consumer(keys)
+    }
+}
+
+impl<'a> From<&'a BearerToken> for Auth<'a> {
+    fn from(token: &'a BearerToken) -> Self {
+        Self::AppOnly(token)
+    }
+}
+
+impl<'a> From<&'a OAuth2Token> for Auth<'a> {
+    fn from(token: &'a OAuth2Token) -> Self {
+        Self::OAuth2User(token)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AuthKind {
+    User,
+    Consumer,
+    AppOnly,
+    OAuth2User,
+}
+
+impl fmt::Display for AuthKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::User => write!(f, "OAuth 1.0a user context"),
+            Self::Consumer => write!(f, "OAuth 1.0a consumer-only"),
+            Self::AppOnly => write!(f, "OAuth 2.0 app-only"),
+            Self::OAuth2User => write!(f, "OAuth 2.0 user context"),
+        }
+    }
+}
+
+/// An endpoint was called with a kind of authentication it does not accept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnsupportedAuth {
+    pub endpoint: &'static str,
+    pub kind: AuthKind,
+}
+
+impl fmt::Display for UnsupportedAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} does not support {} authentication",
+            self.endpoint, self.kind
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedAuth {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn consumer_keys() -> KeyPair {
+        KeyPair::new("ck".to_string(), "cs".to_string())
+    }
+
+    #[test]
+    fn kind() {
+        let keys = consumer_keys();
+        let consumer_only = TokenKeys::new(keys.clone());
+        let user = TokenKeys::new(keys.clone())
+            .oauth_tokens(KeyPair::new("at".to_string(), "as".to_string()));
+        let bearer = BearerToken::new("AAAA".to_string());
+        let oauth2 = OAuth2Token::new("bWVk".to_string());
+
+        assert_eq!(Auth::from(&keys).kind(), AuthKind::Consumer);
+        assert_eq!(Auth::from(&consumer_only).kind(), AuthKind::Consumer);
+        assert_eq!(Auth::from(&user).kind(), AuthKind::User);
+        assert_eq!(Auth::from(&bearer).kind(), AuthKind::AppOnly);
+        assert_eq!(Auth::from(&oauth2).kind(), AuthKind::OAuth2User);
+    }
+
+    #[actix_rt::test]
+    async fn reject_unsupported_auth() {
+        let bearer = BearerToken::new("AAAA".to_string());
+        let error = statuses::update(&bearer, "Hello".to_string())
+            .send()
+            .await
+            .unwrap_err();
+
+        match error {
+            Error::UnsupportedAuth(error) => {
+                assert_eq!(error.kind, AuthKind::AppOnly);
+                assert_eq!(
+                    error.to_string(),
+                    "statuses/update does not support OAuth 2.0 app-only authentication"
+                );
+            }
+            error => panic!("unexpected error: {}", error),
+        }
+    }
+}
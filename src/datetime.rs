@@ -0,0 +1,122 @@
+//! Serde formats for the timestamps in API objects, for use with
+//! `#[serde(with = "...")]`.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serializer};
+use std::fmt;
+
+/// `created_at` of Tweets and users, e.g. `Wed Oct 10 20:19:24 +0000 2018`.
+pub mod created_at {
+    use super::*;
+
+    pub const FORMAT: &str = "%a %b %d %H:%M:%S %z %Y";
+
+    pub fn serialize<S: Serializer>(
+        date_time: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&date_time.format(FORMAT))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        DateTime::parse_from_str(&s, FORMAT)
+            .map(|date_time| date_time.with_timezone(&Utc))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Milliseconds since the epoch as a string, e.g. `created_timestamp` of
+/// direct messages. Numbers are accepted too.
+pub mod timestamp_ms {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        date_time: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&date_time.timestamp_millis())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        deserializer.deserialize_any(TimestampMsVisitor)
+    }
+
+    struct TimestampMsVisitor;
+
+    impl<'de> Visitor<'de> for TimestampMsVisitor {
+        type Value = DateTime<Utc>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a timestamp in milliseconds")
+        }
+
+        fn visit_u64<E: de::Error>(self, ms: u64) -> Result<Self::Value, E> {
+            self.visit_i64(ms as i64)
+        }
+
+        fn visit_i64<E: de::Error>(self, ms: i64) -> Result<Self::Value, E> {
+            Utc.timestamp_millis_opt(ms)
+                .single()
+                .ok_or_else(|| E::custom(format!("timestamp out of range: {}", ms)))
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            let ms = s.parse::<i64>().map_err(E::custom)?;
+            self.visit_i64(ms)
+        }
+    }
+
+    /// Milliseconds since the epoch as a number, e.g. `timestamp_ms` of
+    /// `favorite_events`. Strings are accepted too.
+    pub mod number {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            date_time: &DateTime<Utc>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_i64(date_time.timestamp_millis())
+        }
+
+        pub use super::deserialize;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Timestamps {
+        #[serde(with = "created_at")]
+        created_at: DateTime<Utc>,
+        #[serde(with = "timestamp_ms")]
+        created_timestamp: DateTime<Utc>,
+        #[serde(with = "timestamp_ms::number")]
+        timestamp_ms: DateTime<Utc>,
+    }
+
+    #[test]
+    fn round_trip() {
+        let json = r#"{"created_at":"Wed Oct 10 20:19:24 +0000 2018","created_timestamp":"1539202764000","timestamp_ms":1539202764000}"#;
+        let timestamps: Timestamps = serde_json::from_str(json).unwrap();
+        let expected = Utc.with_ymd_and_hms(2018, 10, 10, 20, 19, 24).unwrap();
+        assert_eq!(timestamps.created_at, expected);
+        assert_eq!(timestamps.created_timestamp, expected);
+        assert_eq!(timestamps.timestamp_ms, expected);
+        assert_eq!(serde_json::to_string(&timestamps).unwrap(), json);
+
+        let number: Timestamps = serde_json::from_str(
+            r#"{"created_at":"Wed Oct 10 22:19:24 +0200 2018","created_timestamp":1539202764000,"timestamp_ms":"1539202764000"}"#,
+        )
+        .unwrap();
+        assert_eq!(number, timestamps);
+    }
+}
@@ -1,5 +1,13 @@
+use crate::auth::UnsupportedAuth;
+use crate::oauth::{CallbackError, InvalidOAuthHeader, OAuth2Error, VerifyError};
+use crate::permalink::InvalidPermalink;
+use crate::pool::PoolError;
+use crate::statuses::ValidationError;
 use actix_web::client::{JsonPayloadError, SendRequestError};
 use actix_web::error::PayloadError;
+use actix_web::http::HeaderMap;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
 use serde_qs::Error as SerdeQsError;
 use std::convert::From;
 use std::fmt;
@@ -42,4 +50,76 @@ impl_error! {
     JsonPayloadError,
     PayloadError,
     SerdeQsError,
+    UnsupportedAuth,
+    OAuth2Error,
+    CallbackError,
+    InvalidOAuthHeader,
+    TwitterError,
+    PoolError,
+    VerifyError,
+    ValidationError,
+    InvalidPermalink,
+}
+
+/// An error response of the Twitter API.
+///
+/// https://developer.twitter.com/en/support/twitter-api/error-troubleshooting
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct TwitterError {
+    #[serde(skip)]
+    pub status: u16,
+    #[serde(skip)]
+    pub rate_limit: Option<RateLimit>,
+    #[serde(default)]
+    pub errors: Vec<ErrorMessage>,
+}
+
+impl TwitterError {
+    /// Sorry, that page does not exist.
+    pub const PAGE_NOT_FOUND: u32 = 34;
+    /// Invalid or expired token.
+    pub const INVALID_OR_EXPIRED_TOKEN: u32 = 89;
+    /// Rate limit exceeded.
+    pub const RATE_LIMIT_EXCEEDED: u32 = 88;
+
+    pub fn has_code(&self, code: u32) -> bool {
+        self.errors.iter().any(|error| error.code == code)
+    }
+}
+
+impl fmt::Display for TwitterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Twitter API responded with status {}", self.status)?;
+        for error in &self.errors {
+            write!(f, "; {} (code {})", error.message, error.code)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TwitterError {}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ErrorMessage {
+    pub code: u32,
+    pub message: String,
+}
+
+/// The `x-rate-limit-*` response headers.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RateLimit {
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let get = |name: &str| headers.get(name)?.to_str().ok()?.parse::<i64>().ok();
+        Some(Self {
+            limit: get("x-rate-limit-limit")? as u32,
+            remaining: get("x-rate-limit-remaining")? as u32,
+            reset: Utc.timestamp_opt(get("x-rate-limit-reset")?, 0).single()?,
+        })
+    }
 }
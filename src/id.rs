@@ -0,0 +1,201 @@
+//! Snowflake ids.
+//!
+//! https://developer.twitter.com/en/docs/twitter-ids
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// Milliseconds since the Unix epoch of the snowflake epoch, 2010-11-04.
+const EPOCH_MS: i64 = 1288834974657;
+const TIMESTAMP_SHIFT: u32 = 22;
+
+/// Ids given the first id that is a snowflake also get the time helpers.
+macro_rules! snowflake {
+    ($(#[$attr:meta])* $name:ident, $first_snowflake:expr) => {
+        snowflake!($(#[$attr])* $name);
+
+        impl $name {
+            const FIRST_SNOWFLAKE: u64 = $first_snowflake;
+
+            /// When the id was generated, if it is a snowflake. Older ids are
+            /// sequential and carry no time.
+            pub fn created_at(self) -> Option<DateTime<Utc>> {
+                if self.0 < Self::FIRST_SNOWFLAKE {
+                    return None;
+                }
+                let ms = (self.0 >> TIMESTAMP_SHIFT) as i64 + EPOCH_MS;
+                Utc.timestamp_millis_opt(ms).single()
+            }
+
+            /// The smallest id generated at or after `date_time`.
+            pub fn first_at(date_time: DateTime<Utc>) -> Self {
+                let ms = (date_time.timestamp_millis() - EPOCH_MS).max(0) as u64;
+                Self(ms << TIMESTAMP_SHIFT)
+            }
+
+            /// A `since_id` selecting ids generated at or after `date_time`.
+            pub fn since_id(date_time: DateTime<Utc>) -> Self {
+                Self(Self::first_at(date_time).0.saturating_sub(1))
+            }
+
+            /// A `max_id` selecting ids generated before `date_time`.
+            pub fn max_id(date_time: DateTime<Utc>) -> Self {
+                Self(Self::first_at(date_time).0.saturating_sub(1))
+            }
+        }
+    };
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        ///
+        /// Deserializes from both the numeric `id` and the `id_str` form.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub u64);
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl PartialEq<u64> for $name {
+            fn eq(&self, other: &u64) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<$name> for u64 {
+            fn eq(&self, other: &$name) -> bool {
+                *self == other.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map(Self)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u64(self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any(IdVisitor).map(Self)
+            }
+        }
+    };
+}
+
+snowflake!(
+    /// The id of a Tweet.
+    TweetId,
+    29_700_859_247
+);
+
+snowflake!(
+    /// The id of a user.
+    UserId,
+    1 << 32
+);
+
+snowflake!(
+    /// The id of uploaded media, as in `media_ids`.
+    MediaId
+);
+
+snowflake!(
+    /// The id of a filter rule of a stream or search, as in `matching_rules`.
+    RuleId
+);
+
+struct IdVisitor;
+
+impl<'de> Visitor<'de> for IdVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an id as a number or a string")
+    }
+
+    fn visit_u64<E: de::Error>(self, id: u64) -> Result<Self::Value, E> {
+        Ok(id)
+    }
+
+    fn visit_i64<E: de::Error>(self, id: i64) -> Result<Self::Value, E> {
+        if id < 0 {
+            return Err(E::custom(format!("negative id: {}", id)));
+        }
+        Ok(id as u64)
+    }
+
+    fn visit_str<E: de::Error>(self, id: &str) -> Result<Self::Value, E> {
+        id.parse().map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize() {
+        let ids: Vec<TweetId> =
+            serde_json::from_str(r#"[1050118621198921728, "1050118621198921728"]"#).unwrap();
+        assert_eq!(ids[0], ids[1]);
+        assert_eq!(ids[0], 1050118621198921728);
+        assert!(serde_json::from_str::<TweetId>("-1").is_err());
+        assert!(serde_json::from_str::<TweetId>(r#""abc""#).is_err());
+        assert_eq!(
+            serde_json::to_string(&ids[0]).unwrap(),
+            "1050118621198921728"
+        );
+    }
+
+    #[test]
+    fn created_at() {
+        let created_at = TweetId(1050118621198921728).created_at().unwrap();
+        assert_eq!(
+            created_at,
+            Utc.with_ymd_and_hms(2018, 10, 10, 20, 19, 24).unwrap()
+                + chrono::Duration::milliseconds(211)
+        );
+        assert_eq!(TweetId(20).created_at(), None);
+        assert_eq!(UserId(2244994945).created_at(), None);
+        assert!(UserId(1050118621198921728).created_at().is_some());
+    }
+
+    #[test]
+    fn bounds() {
+        let start = Utc.with_ymd_and_hms(2018, 10, 10, 20, 19, 24).unwrap();
+        let end = start + chrono::Duration::seconds(1);
+        let since_id = TweetId::since_id(start);
+        let max_id = TweetId::max_id(end);
+        let id = TweetId(1050118621198921728);
+        assert!(since_id < id && id <= max_id);
+        assert!(since_id.created_at().unwrap() < start);
+        assert_eq!(TweetId::first_at(start).created_at(), Some(start));
+        assert!(max_id.created_at().unwrap() < end);
+        assert_eq!(TweetId(max_id.0 + 1).created_at(), Some(end));
+    }
+}
@@ -1,13 +1,26 @@
+pub mod account;
+pub mod account_activity;
+mod auth;
+pub mod datetime;
 mod error;
+mod id;
 mod macros;
 pub mod oauth;
+pub mod permalink;
+pub mod pool;
 pub mod statuses;
+pub mod text;
 mod utils;
 
-pub use error::Error;
-use serde::Deserialize;
+pub use auth::{Auth, AuthKind, UnsupportedAuth};
+use chrono::{DateTime, Utc};
+pub use error::{Error, ErrorMessage, RateLimit, TwitterError};
+pub use id::{MediaId, RuleId, TweetId, UserId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
 pub struct KeyPair {
     key: String,
     secret: String,
@@ -19,10 +32,24 @@ impl KeyPair {
     }
 }
 
-#[derive(Clone)]
+/// Leaves the secret out of logs and panic messages.
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("key", &self.key)
+            .field("secret", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
 pub struct TokenKeys {
     consumer_keys: KeyPair,
     oauth_tokens: Option<KeyPair>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    user_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    screen_name: Option<String>,
 }
 
 impl TokenKeys {
@@ -30,6 +57,8 @@ impl TokenKeys {
         Self {
             consumer_keys,
             oauth_tokens: None,
+            user_id: None,
+            screen_name: None,
         }
     }
 
@@ -37,15 +66,227 @@ impl TokenKeys {
         self.oauth_tokens = Some(oauth_tokens);
         self
     }
+
+    /// Records the account the access token belongs to.
+    pub fn user(mut self, user_id: u64, screen_name: String) -> Self {
+        self.user_id = Some(user_id);
+        self.screen_name = Some(screen_name);
+        self
+    }
+
+    pub fn user_id(&self) -> Option<u64> {
+        self.user_id
+    }
+
+    pub fn screen_name(&self) -> Option<&str> {
+        self.screen_name.as_deref()
+    }
+}
+
+impl fmt::Debug for TokenKeys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TokenKeys")
+            .field("consumer_keys", &self.consumer_keys)
+            .field("oauth_tokens", &self.oauth_tokens)
+            .field("user_id", &self.user_id)
+            .field("screen_name", &self.screen_name)
+            .finish()
+    }
+}
+
+/// An OAuth 2.0 bearer token for application-only authentication.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+pub struct BearerToken {
+    access_token: String,
+}
+
+impl BearerToken {
+    pub fn new(access_token: String) -> Self {
+        Self { access_token }
+    }
+
+    pub fn access_token(&self) -> &str {
+        &self.access_token
+    }
+}
+
+impl fmt::Debug for BearerToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BearerToken")
+            .field("access_token", &format_args!("<redacted>"))
+            .finish()
+    }
 }
 
-// TODO
-#[derive(Deserialize, Clone, Debug)]
-pub struct User {}
+/// An OAuth 2.0 user access token.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+pub struct OAuth2Token {
+    access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scopes: Vec<String>,
+}
+
+impl OAuth2Token {
+    pub fn new(access_token: String) -> Self {
+        Self {
+            access_token,
+            refresh_token: None,
+            expires_at: None,
+            scopes: Vec::new(),
+        }
+    }
+
+    /// For tokens restored from storage, so they can still be refreshed.
+    pub fn with_refresh_token(mut self, refresh_token: String) -> Self {
+        self.refresh_token = Some(refresh_token);
+        self
+    }
 
+    pub fn with_expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    pub fn access_token(&self) -> &str {
+        &self.access_token
+    }
+
+    /// Only issued with the `offline.access` scope.
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.refresh_token.as_deref()
+    }
+
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|expires_at| expires_at <= Utc::now())
+            .unwrap_or(false)
+    }
+}
+
+impl fmt::Debug for OAuth2Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let redacted = format_args!("<redacted>");
+        f.debug_struct("OAuth2Token")
+            .field("access_token", &redacted)
+            .field(
+                "refresh_token",
+                &self.refresh_token.as_ref().map(|_| redacted),
+            )
+            .field("expires_at", &self.expires_at)
+            .field("scopes", &self.scopes)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct User {
+    pub id: UserId,
+    pub id_str: String,
+    pub name: String,
+    pub screen_name: String,
+    pub location: Option<String>,
+    pub url: Option<String>,
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entities: Option<UserEntities>,
+    pub protected: bool,
+    pub verified: bool,
+    pub followers_count: u64,
+    pub friends_count: u64,
+    pub listed_count: u64,
+    pub favourites_count: u64,
+    pub statuses_count: u64,
+    #[serde(with = "crate::datetime::created_at")]
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_banner_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_image_url: Option<String>,
+    pub profile_image_url_https: String,
+    pub default_profile: bool,
+    pub default_profile_image: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub withheld_in_countries: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withheld_scope: Option<String>,
+    /// Only present with `include_email` and the email permission.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// The most recent Tweet, unless the user is embedded in one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Box<Tweet>>,
+    // Relationship to the authenticating user, when there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub following: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow_request_sent: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<bool>,
+    /// Unmodeled fields such as `translator_type`.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// URLs in the profile `url` and `description`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct UserEntities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<ProfileEntity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<ProfileEntity>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Profile fields only carry `urls`, unlike the `Entity` of a Tweet.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ProfileEntity {
+    #[serde(default)]
+    pub urls: Vec<Url>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// `[longitude, latitude]`, in GeoJSON order.
 pub type Coordinate = [f64; 2];
 
-#[derive(Deserialize, Clone, Debug)]
+/// A GeoJSON `Point`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Coordinates {
+    pub r#type: String,
+    pub coordinates: Coordinate,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Coordinates {
+    pub fn longitude(&self) -> f64 {
+        self.coordinates[0]
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.coordinates[1]
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Place {
     pub id: String,
     pub url: String,
@@ -55,40 +296,324 @@ pub struct Place {
     pub country_code: String,
     pub country: String,
     pub bounding_box: BoundingBox,
-    // pub attributes: Object,
+    /// e.g. `street_address` or `phone` of points of interest.
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+    /// e.g. `contained_within`.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+/// A GeoJSON `Polygon`: rings of `[longitude, latitude]`, the first being the
+/// outline. Places have a single ring of 4 corners.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct BoundingBox {
-    pub coordinates: [[[f64; 2]; 2]; 2],
+    pub coordinates: Vec<Vec<Coordinate>>,
     pub r#type: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
-// TODO
-#[derive(Deserialize, Clone, Debug)]
-pub struct Entity {}
+impl BoundingBox {
+    fn outline(&self) -> &[Coordinate] {
+        match self.coordinates.first() {
+            // A closed ring repeats its first point.
+            Some(ring) if ring.len() > 1 && ring.first() == ring.last() => &ring[..ring.len() - 1],
+            Some(ring) => ring,
+            None => &[],
+        }
+    }
 
-// TODO
-#[derive(Deserialize, Clone, Debug)]
-pub struct ExtendedEntity {}
+    /// The mean of the corners.
+    pub fn centroid(&self) -> Option<Coordinate> {
+        let outline = self.outline();
+        if outline.is_empty() {
+            return None;
+        }
+        let n = outline.len() as f64;
+        let (lon, lat) = outline.iter().fold((0.0, 0.0), |(lon, lat), point| {
+            (lon + point[0], lat + point[1])
+        });
+        Some([lon / n, lat / n])
+    }
 
-// TODO
-#[derive(Deserialize, Clone, Debug)]
-pub struct Rule {}
+    /// Whether `point` lies within the extent of the corners, edges included.
+    pub fn contains(&self, point: Coordinate) -> bool {
+        let outline = self.outline();
+        if outline.is_empty() {
+            return false;
+        }
+        let (mut min, mut max) = (outline[0], outline[0]);
+        for corner in outline {
+            min = [min[0].min(corner[0]), min[1].min(corner[1])];
+            max = [max[0].max(corner[0]), max[1].max(corner[1])];
+        }
+        (min[0]..=max[0]).contains(&point[0]) && (min[1]..=max[1]).contains(&point[1])
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct Entity {
+    #[serde(default)]
+    pub hashtags: Vec<Hashtag>,
+    #[serde(default)]
+    pub urls: Vec<Url>,
+    #[serde(default)]
+    pub user_mentions: Vec<UserMention>,
+    #[serde(default)]
+    pub symbols: Vec<Symbol>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub polls: Vec<Poll>,
+    /// Only the first photo; see `Tweet.extended_entities` for all media.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media: Vec<Media>,
+    /// Entity kinds not listed above.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// `indices` are offsets in code points, see `text::slice`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Hashtag {
+    pub indices: [usize; 2],
+    pub text: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// A cashtag such as `$TWTR`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Symbol {
+    pub indices: [usize; 2],
+    pub text: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Url {
+    pub indices: [usize; 2],
+    /// The t.co URL in the text.
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expanded_url: Option<String>,
+    /// e.g. `unwound` with the Enhanced URLs enrichment.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct UserMention {
+    pub indices: [usize; 2],
+    pub id: UserId,
+    pub id_str: String,
+    pub name: String,
+    pub screen_name: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Poll {
+    pub options: Vec<PollOption>,
+    pub end_datetime: String,
+    pub duration_minutes: u32,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct PollOption {
+    pub position: u32,
+    pub text: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+macro_rules! impl_indices {
+    ($($ty:ident),*) => {
+        $(impl text::Indices for $ty {
+            fn indices(&self) -> [usize; 2] {
+                self.indices
+            }
+        })*
+    };
+}
+
+impl_indices!(Hashtag, Symbol, Url, UserMention, Media);
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ExtendedEntity {
+    pub media: Vec<Media>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Media {
+    pub id: MediaId,
+    pub id_str: String,
+    pub indices: [usize; 2],
+    pub r#type: MediaType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_url: Option<String>,
+    pub media_url_https: String,
+    /// The t.co URL in the text.
+    pub url: String,
+    pub display_url: String,
+    pub expanded_url: String,
+    pub sizes: Sizes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ext_alt_text: Option<String>,
+    /// The original Tweet, when the media was reused from it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_status_id: Option<TweetId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_info: Option<VideoInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_media_info: Option<AdditionalMediaInfo>,
+    /// e.g. `media_key` or `features`.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Media {
+    /// See `VideoInfo::best_mp4`.
+    pub fn best_mp4(&self) -> Option<&Variant> {
+        self.video_info.as_ref()?.best_mp4()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaType {
+    Photo,
+    Video,
+    AnimatedGif,
+    /// A type this crate doesn't know yet. Serializes as `"unknown"`.
+    #[serde(other)]
+    Unknown,
+}
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Sizes {
+    pub thumb: Size,
+    pub small: Size,
+    pub medium: Size,
+    pub large: Size,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Size {
+    pub w: u32,
+    pub h: u32,
+    /// `fit` or `crop`
+    pub resize: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct VideoInfo {
+    pub aspect_ratio: [u32; 2],
+    /// Absent for animated GIFs.
+    pub duration_millis: Option<u64>,
+    pub variants: Vec<Variant>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl VideoInfo {
+    /// The `video/mp4` variant with the highest bitrate.
+    pub fn best_mp4(&self) -> Option<&Variant> {
+        self.variants
+            .iter()
+            .filter(|variant| variant.content_type == "video/mp4")
+            .max_by_key(|variant| variant.bitrate.unwrap_or(0))
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Variant {
+    /// Absent for HLS playlists.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<u64>,
+    pub content_type: String,
+    pub url: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct AdditionalMediaInfo {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub embeddable: Option<bool>,
+    #[serde(default)]
+    pub monetizable: bool,
+    /// e.g. `call_to_actions`.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// A filter rule of an enterprise or premium stream that the Tweet matched.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Rule {
+    /// `null` for rules created without a tag.
+    pub tag: Option<String>,
+    /// Absent from activity-format `gnip.matching_rules`, which carry the rule
+    /// `value` instead, and a string in v2 streams.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<RuleId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_str: Option<String>,
+    /// e.g. the rule `value` in activity-format payloads.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ExtendedTweet {
+    pub full_text: String,
+    pub display_text_range: Option<[usize; 2]>,
+    pub entities: Entity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended_entities: Option<ExtendedEntity>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Tweet {
-    pub created_at: String,
-    pub id: u64,
+    #[serde(with = "crate::datetime::created_at")]
+    pub created_at: DateTime<Utc>,
+    pub id: TweetId,
+    /// Empty in extended mode; see `complete_text`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub text: String,
+    /// Only in extended mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_text: Option<String>,
+    /// The code points of the text that are not leading mentions or trailing
+    /// media links.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_text_range: Option<[usize; 2]>,
+    /// The complete Tweet when `truncated` in compatibility mode, as streamed
+    /// and sent to webhooks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended_tweet: Option<ExtendedTweet>,
     pub entities: Entity,
     pub source: String,
     pub truncated: bool,
-    pub in_reply_to_status_id: Option<u64>,
-    pub in_reply_to_user_id: Option<u64>,
+    pub in_reply_to_status_id: Option<TweetId>,
+    pub in_reply_to_user_id: Option<UserId>,
     pub in_reply_to_screen_name: Option<String>,
     pub user: User,
-    pub coordinates: Option<Coordinate>,
+    pub coordinates: Option<Coordinates>,
     pub place: Option<Place>,
     pub is_quote_status: bool,
     pub retweet_count: u64,
@@ -96,13 +621,75 @@ pub struct Tweet {
     pub favorited: Option<bool>,
     pub retweeted: bool,
     pub lang: Option<String>,
-    pub quoted_status_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quoted_status_id: Option<TweetId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub quoted_status: Option<Box<Tweet>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub retweeted_status: Option<Box<Tweet>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub quote_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub extended_entities: Option<ExtendedEntity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub possibly_sensitive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub matching_rules: Option<Vec<Rule>>,
+    /// Fields not modeled above, e.g. `id_str` or `contributors`, so that the
+    /// Tweet serializes back as it was received.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Tweet {
+    /// The untruncated text, whichever `tweet_mode` the Tweet was fetched in.
+    ///
+    /// For a retweet this is the text of `retweeted_status`, without the
+    /// `RT @screen_name: ` prefix, as the retweet's own text is truncated.
+    pub fn complete_text(&self) -> &str {
+        if let Some(retweeted_status) = &self.retweeted_status {
+            return retweeted_status.complete_text();
+        }
+        match (&self.extended_tweet, &self.full_text) {
+            (Some(extended_tweet), _) => &extended_tweet.full_text,
+            (None, Some(full_text)) => full_text,
+            (None, None) => &self.text,
+        }
+    }
+
+    /// Tags of the `matching_rules` that have one.
+    pub fn matching_tags(&self) -> impl Iterator<Item = &str> {
+        self.matching_rules
+            .iter()
+            .flatten()
+            .filter_map(|rule| rule.tag.as_deref())
+    }
+
+    /// The entities indexing into `complete_text`, so those of
+    /// `retweeted_status` for a retweet.
+    pub fn complete_entities(&self) -> &Entity {
+        if let Some(retweeted_status) = &self.retweeted_status {
+            return retweeted_status.complete_entities();
+        }
+        match &self.extended_tweet {
+            Some(extended_tweet) => &extended_tweet.entities,
+            None => &self.entities,
+        }
+    }
+
+    /// The `display_text_range` of `complete_text`, so that of
+    /// `retweeted_status` for a retweet.
+    pub fn complete_display_text_range(&self) -> Option<[usize; 2]> {
+        if let Some(retweeted_status) = &self.retweeted_status {
+            return retweeted_status.complete_display_text_range();
+        }
+        match &self.extended_tweet {
+            Some(extended_tweet) => extended_tweet.display_text_range,
+            None => self.display_text_range,
+        }
+    }
 }
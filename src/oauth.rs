@@ -1,7 +1,19 @@
+mod callback;
+mod oauth2;
+mod verifier;
+
 use crate::error::*;
 use crate::utils::*;
 use crate::*;
-use serde::Deserialize;
+pub use callback::{callback_server, Callback, CallbackError, CallbackListener, CallbackServer};
+pub use oauth2::{
+    authorize_oauth2, exchange_code, refresh_oauth2_token, revoke_oauth2_token, CodeVerifier,
+    ExchangeCode, OAuth2AuthorizationRequest, OAuth2Authorize, OAuth2Client, OAuth2Error,
+    OAuth2TokenResponse, RefreshOAuth2Token, RevokeOAuth2Token, Scope, TokenTypeHint,
+};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+pub use verifier::{RequestVerifier, SecretStore, VerifiedRequest, VerifyError};
 
 #[derive(Clone, Copy)]
 pub enum AccessType {
@@ -18,7 +30,77 @@ impl std::fmt::Display for AccessType {
     }
 }
 
-pub fn request_token(keys: &KeyPair, oauth_callback: String) -> RequestToken {
+/// What the tokens are permitted to do, as reported by the `x-access-level`
+/// response header.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AccessLevel {
+    Read,
+    ReadWrite,
+    ReadWriteDirectMessages,
+}
+
+impl AccessLevel {
+    pub fn can_write(&self) -> bool {
+        *self != Self::Read
+    }
+
+    pub fn can_access_direct_messages(&self) -> bool {
+        *self == Self::ReadWriteDirectMessages
+    }
+}
+
+impl std::fmt::Display for AccessLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::ReadWrite => write!(f, "read-write"),
+            Self::ReadWriteDirectMessages => write!(f, "read-write-directmessages"),
+        }
+    }
+}
+
+impl std::str::FromStr for AccessLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(Self::Read),
+            "read-write" => Ok(Self::ReadWrite),
+            "read-write-directmessages" => Ok(Self::ReadWriteDirectMessages),
+            _ => Err(format!("unknown access level: {}", s)),
+        }
+    }
+}
+
+impl From<AccessType> for AccessLevel {
+    fn from(access_type: AccessType) -> Self {
+        match access_type {
+            AccessType::Read => Self::Read,
+            AccessType::Write => Self::ReadWrite,
+        }
+    }
+}
+
+/// Special modes of `oauth/request_token` and `oauth/access_token`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum XAuthMode {
+    /// Reverse Auth: obtains tokens for this application from another one
+    /// the user already authorized.
+    ReverseAuth,
+    /// xAuth: exchanges a username and password for tokens.
+    ClientAuth,
+}
+
+impl std::fmt::Display for XAuthMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::ReverseAuth => write!(f, "reverse_auth"),
+            Self::ClientAuth => write!(f, "client_auth"),
+        }
+    }
+}
+
+pub fn request_token(keys: &KeyPair, oauth_callback: String) -> RequestToken<'_> {
     RequestToken::new(keys, oauth_callback)
 }
 
@@ -44,17 +126,18 @@ impl<'a> RequestToken<'a> {
 
     pub async fn send(self) -> Result<RequestTokenResponse> {
         let url = "https://api.twitter.com/oauth/request_token";
-        let tokens = TokenKeys::new(self.consumer_keys.clone());
         let mut request = Request::post(url);
         request.oauth_param("oauth_callback", &self.oauth_callback);
         _opt_query!(self, request, x_auth_access_type);
-        let mut res = request.send(&tokens).await?;
+        let mut res = request
+            .send_checked(Auth::Consumer(self.consumer_keys))
+            .await?;
         let body = res.body().await?;
         Ok(serde_qs::from_bytes(body.as_ref())?)
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct RequestTokenResponse {
     pub oauth_token: String,
     pub oauth_token_secret: String,
@@ -69,20 +152,54 @@ impl RequestTokenResponse {
     pub fn get_redirect_url(&self) -> String {
         self.authorize().into_url()
     }
+
+    /// Sign in with Twitter: users who already authorized the application are
+    /// redirected back without being asked again.
+    pub fn authenticate(&self) -> Authenticate {
+        Authenticate::new(self.oauth_token.clone())
+    }
+
+    pub fn get_authenticate_url(&self) -> String {
+        self.authenticate().into_url()
+    }
+}
+
+/// `oauth/authorize` or `oauth/authenticate`.
+pub trait RedirectEndpoint {
+    const URL: &'static str;
 }
 
-pub struct Authorize {
+pub enum AuthorizeEndpoint {}
+
+impl RedirectEndpoint for AuthorizeEndpoint {
+    const URL: &'static str = "https://api.twitter.com/oauth/authorize";
+}
+
+pub enum AuthenticateEndpoint {}
+
+impl RedirectEndpoint for AuthenticateEndpoint {
+    const URL: &'static str = "https://api.twitter.com/oauth/authenticate";
+}
+
+/// The URL users are sent to with the request token. Both endpoints take the
+/// same options.
+pub struct UserRedirect<E> {
     oauth_token: String,
     force_login: Option<bool>,
     screen_name: Option<String>,
+    endpoint: PhantomData<E>,
 }
 
-impl Authorize {
+pub type Authorize = UserRedirect<AuthorizeEndpoint>;
+pub type Authenticate = UserRedirect<AuthenticateEndpoint>;
+
+impl<E: RedirectEndpoint> UserRedirect<E> {
     pub fn new(oauth_token: String) -> Self {
         Self {
             oauth_token,
             force_login: None,
             screen_name: None,
+            endpoint: PhantomData,
         }
     }
 
@@ -97,10 +214,7 @@ impl Authorize {
     }
 
     pub fn into_url(self) -> String {
-        let mut url = format!(
-            "https://api.twitter.com/oauth/authorize?oauth_token={}",
-            self.oauth_token
-        );
+        let mut url = format!("{}?oauth_token={}", E::URL, self.oauth_token);
         if let Some(force_login) = self.force_login {
             url += &format!("&force_login={}", force_login);
         }
@@ -111,7 +225,11 @@ impl Authorize {
     }
 }
 
-pub fn access_token(keys: &KeyPair, oauth_token: String, oauth_verifier: String) -> AccessToken {
+pub fn access_token(
+    keys: &KeyPair,
+    oauth_token: String,
+    oauth_verifier: String,
+) -> AccessToken<'_> {
     AccessToken::new(keys, oauth_token, oauth_verifier)
 }
 
@@ -132,18 +250,17 @@ impl<'a> AccessToken<'a> {
 
     pub async fn send(self) -> Result<AccessTokenResponse> {
         let url = "https://api.twitter.com/oauth/access_token";
-        let tokens = TokenKeys::new(self.consumer_keys.clone());
         let mut res = Request::post(url)
             .oauth_param("oauth_token", &self.oauth_token)
             .oauth_param("oauth_verifier", &self.oauth_verifier)
-            .send(&tokens)
+            .send_checked(Auth::Consumer(self.consumer_keys))
             .await?;
         let body = res.body().await?;
         Ok(serde_qs::from_bytes(body.as_ref())?)
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct AccessTokenResponse {
     pub oauth_token: String,
     pub oauth_token_secret: String,
@@ -151,7 +268,213 @@ pub struct AccessTokenResponse {
     pub screen_name: String,
 }
 
-pub fn invalidate_token(tokens: &TokenKeys) -> InvalidateToken {
+impl AccessTokenResponse {
+    /// Builds user context credentials, keeping `user_id` and `screen_name`
+    /// alongside the tokens.
+    pub fn into_token_keys(self, consumer_keys: KeyPair) -> TokenKeys {
+        TokenKeys::new(consumer_keys)
+            .oauth_tokens(KeyPair::new(self.oauth_token, self.oauth_token_secret))
+            .user(self.user_id, self.screen_name)
+    }
+}
+
+pub fn reverse_auth(keys: &KeyPair) -> ReverseAuth<'_> {
+    ReverseAuth::new(keys)
+}
+
+/// `POST oauth/request_token` with `x_auth_mode=reverse_auth`
+///
+/// https://developer.twitter.com/en/docs/authentication/oauth-1-0a/reverse-auth
+pub struct ReverseAuth<'a> {
+    consumer_keys: &'a KeyPair,
+}
+
+impl<'a> ReverseAuth<'a> {
+    pub fn new(consumer_keys: &'a KeyPair) -> Self {
+        Self { consumer_keys }
+    }
+
+    pub async fn send(self) -> Result<ReverseAuthParameters> {
+        let url = "https://api.twitter.com/oauth/request_token";
+        let mut request = Request::post(url);
+        request
+            .parameter("x_auth_mode", XAuthMode::ReverseAuth)
+            .form_body();
+        let mut res = request
+            .send_checked(Auth::Consumer(self.consumer_keys))
+            .await?;
+        let body = res.body().await?;
+        let header = String::from_utf8_lossy(body.as_ref());
+        Ok(header.parse()?)
+    }
+}
+
+/// The signed `OAuth ...` string returned by the first step of Reverse Auth,
+/// to be passed to the device holding the user's tokens.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ReverseAuthParameters {
+    header: String,
+    params: Vec<(String, String)>,
+}
+
+impl ReverseAuthParameters {
+    pub fn as_str(&self) -> &str {
+        &self.header
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn oauth_consumer_key(&self) -> Option<&str> {
+        self.get("oauth_consumer_key")
+    }
+
+    pub fn oauth_token(&self) -> Option<&str> {
+        self.get("oauth_token")
+    }
+}
+
+impl std::str::FromStr for ReverseAuthParameters {
+    type Err = InvalidOAuthHeader;
+
+    fn from_str(header: &str) -> std::result::Result<Self, Self::Err> {
+        let header = header.trim();
+        let params = parse_oauth_header(header).ok_or_else(|| InvalidOAuthHeader {
+            header: header.to_string(),
+        })?;
+        Ok(Self {
+            header: header.to_string(),
+            params,
+        })
+    }
+}
+
+impl std::convert::TryFrom<String> for ReverseAuthParameters {
+    type Error = InvalidOAuthHeader;
+
+    fn try_from(header: String) -> std::result::Result<Self, Self::Error> {
+        header.parse()
+    }
+}
+
+impl From<ReverseAuthParameters> for String {
+    fn from(parameters: ReverseAuthParameters) -> Self {
+        parameters.header
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvalidOAuthHeader {
+    pub header: String,
+}
+
+impl std::fmt::Display for InvalidOAuthHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid OAuth header: {:?}", self.header)
+    }
+}
+
+impl std::error::Error for InvalidOAuthHeader {}
+
+pub fn reverse_auth_access_token<'a>(
+    tokens: &'a TokenKeys,
+    x_reverse_auth_target: String,
+    x_reverse_auth_parameters: &'a ReverseAuthParameters,
+) -> ReverseAuthAccessToken<'a> {
+    ReverseAuthAccessToken::new(tokens, x_reverse_auth_target, x_reverse_auth_parameters)
+}
+
+/// `POST oauth/access_token` with `x_reverse_auth_parameters`
+///
+/// Signed with the user's tokens of the application that already holds them;
+/// `x_reverse_auth_target` is the consumer key of the application requesting access.
+pub struct ReverseAuthAccessToken<'a> {
+    tokens: &'a TokenKeys,
+    x_reverse_auth_target: String,
+    x_reverse_auth_parameters: &'a ReverseAuthParameters,
+}
+
+impl<'a> ReverseAuthAccessToken<'a> {
+    pub fn new(
+        tokens: &'a TokenKeys,
+        x_reverse_auth_target: String,
+        x_reverse_auth_parameters: &'a ReverseAuthParameters,
+    ) -> Self {
+        Self {
+            tokens,
+            x_reverse_auth_target,
+            x_reverse_auth_parameters,
+        }
+    }
+
+    pub async fn send(self) -> Result<AccessTokenResponse> {
+        let url = "https://api.twitter.com/oauth/access_token";
+        let mut request = Request::post(url);
+        request
+            .parameter("x_reverse_auth_target", self.x_reverse_auth_target)
+            .parameter(
+                "x_reverse_auth_parameters",
+                self.x_reverse_auth_parameters.as_str(),
+            )
+            .form_body();
+        let mut res = request.send_checked(self.tokens).await?;
+        let body = res.body().await?;
+        Ok(serde_qs::from_bytes(body.as_ref())?)
+    }
+}
+
+pub fn xauth_access_token(
+    keys: &KeyPair,
+    x_auth_username: String,
+    x_auth_password: String,
+) -> XAuthAccessToken<'_> {
+    XAuthAccessToken::new(keys, x_auth_username, x_auth_password)
+}
+
+/// `POST oauth/access_token` with `x_auth_mode=client_auth`
+///
+/// Only available to applications granted xAuth access.
+pub struct XAuthAccessToken<'a> {
+    consumer_keys: &'a KeyPair,
+    x_auth_username: String,
+    x_auth_password: String,
+}
+
+impl<'a> XAuthAccessToken<'a> {
+    pub fn new(
+        consumer_keys: &'a KeyPair,
+        x_auth_username: String,
+        x_auth_password: String,
+    ) -> Self {
+        Self {
+            consumer_keys,
+            x_auth_username,
+            x_auth_password,
+        }
+    }
+
+    pub async fn send(self) -> Result<AccessTokenResponse> {
+        let url = "https://api.twitter.com/oauth/access_token";
+        let mut request = Request::post(url);
+        request
+            .parameter("x_auth_username", self.x_auth_username)
+            .parameter("x_auth_password", self.x_auth_password)
+            .parameter("x_auth_mode", XAuthMode::ClientAuth)
+            .form_body();
+        let mut res = request
+            .send_checked(Auth::Consumer(self.consumer_keys))
+            .await?;
+        let body = res.body().await?;
+        Ok(serde_qs::from_bytes(body.as_ref())?)
+    }
+}
+
+pub fn invalidate_token(tokens: &TokenKeys) -> InvalidateToken<'_> {
     InvalidateToken::new(tokens)
 }
 
@@ -166,7 +489,79 @@ impl<'a> InvalidateToken<'a> {
 
     pub async fn send(self) -> Result<()> {
         let url = "https://api.twitter.com/1.1/oauth/invalidate_token";
-        let _res = Request::post(url).send(self.tokens).await?;
+        let _res = Request::post(url).send_checked(self.tokens).await?;
+        Ok(())
+    }
+}
+
+pub fn obtain_bearer_token(keys: &KeyPair) -> ObtainBearerToken<'_> {
+    ObtainBearerToken::new(keys)
+}
+
+/// `POST oauth2/token`
+pub struct ObtainBearerToken<'a> {
+    consumer_keys: &'a KeyPair,
+}
+
+impl<'a> ObtainBearerToken<'a> {
+    pub fn new(consumer_keys: &'a KeyPair) -> Self {
+        Self { consumer_keys }
+    }
+
+    pub async fn send(self) -> Result<BearerTokenResponse> {
+        let url = "https://api.twitter.com/oauth2/token";
+        let mut request = Request::post(url);
+        request
+            .parameter("grant_type", "client_credentials")
+            .form_body();
+        request
+            .send_json(Authorization::Basic(self.consumer_keys))
+            .await
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct BearerTokenResponse {
+    pub token_type: String,
+    pub access_token: String,
+}
+
+impl BearerTokenResponse {
+    pub fn into_bearer_token(self) -> BearerToken {
+        BearerToken::new(self.access_token)
+    }
+}
+
+pub fn invalidate_bearer_token<'a>(
+    keys: &'a KeyPair,
+    token: &'a BearerToken,
+) -> InvalidateBearerToken<'a> {
+    InvalidateBearerToken::new(keys, token)
+}
+
+/// `POST oauth2/invalidate_token`
+pub struct InvalidateBearerToken<'a> {
+    consumer_keys: &'a KeyPair,
+    token: &'a BearerToken,
+}
+
+impl<'a> InvalidateBearerToken<'a> {
+    pub fn new(consumer_keys: &'a KeyPair, token: &'a BearerToken) -> Self {
+        Self {
+            consumer_keys,
+            token,
+        }
+    }
+
+    pub async fn send(self) -> Result<()> {
+        let url = "https://api.twitter.com/oauth2/invalidate_token";
+        let mut request = Request::post(url);
+        request
+            .parameter("access_token", self.token.access_token())
+            .form_body();
+        let _res = request
+            .send_checked(Authorization::Basic(self.consumer_keys))
+            .await?;
         Ok(())
     }
 }
@@ -184,4 +579,151 @@ mod tests {
         };
         assert_eq!(response.get_redirect_url(), "https://api.twitter.com/oauth/authorize?oauth_token=Z6eEdO8MOmk394WozF5oKyuAv855l4Mlqo7hhlSLik");
     }
+
+    #[test]
+    fn authenticate_url() {
+        let response = RequestTokenResponse {
+            oauth_token: "Z6eEdO8MOmk394WozF5oKyuAv855l4Mlqo7hhlSLik".to_string(),
+            oauth_token_secret: "".to_string(),
+            oauth_callback_confirmed: true,
+        };
+        assert_eq!(response.get_authenticate_url(), "https://api.twitter.com/oauth/authenticate?oauth_token=Z6eEdO8MOmk394WozF5oKyuAv855l4Mlqo7hhlSLik");
+        assert_eq!(
+            response
+                .authenticate()
+                .force_login(true)
+                .screen_name("Ladies + Gentlemen".to_string())
+                .into_url(),
+            "https://api.twitter.com/oauth/authenticate?oauth_token=Z6eEdO8MOmk394WozF5oKyuAv855l4Mlqo7hhlSLik&force_login=true&screen_name=Ladies%20%2B%20Gentlemen"
+        );
+    }
+
+    #[test]
+    fn access_token_response_into_token_keys() {
+        let body = "oauth_token=6253282-eWudHldSbIaelX7swmsiHImEL4KinwaGloHANdrY&oauth_token_secret=2EEfA6BG5ly3sR3XjE0IBSnlQu4ZrUzPiYTmrkVU&user_id=6253282&screen_name=twitterapi";
+        let response: AccessTokenResponse = serde_qs::from_str(body).unwrap();
+        let consumer_keys = KeyPair::new("consumer".to_string(), "secret".to_string());
+        let tokens = response.into_token_keys(consumer_keys.clone());
+
+        assert_eq!(tokens.user_id(), Some(6253282));
+        assert_eq!(tokens.screen_name(), Some("twitterapi"));
+        assert_eq!(
+            tokens,
+            TokenKeys::new(consumer_keys)
+                .oauth_tokens(KeyPair::new(
+                    "6253282-eWudHldSbIaelX7swmsiHImEL4KinwaGloHANdrY".to_string(),
+                    "2EEfA6BG5ly3sR3XjE0IBSnlQu4ZrUzPiYTmrkVU".to_string(),
+                ))
+                .user(6253282, "twitterapi".to_string())
+        );
+    }
+
+    #[test]
+    fn access_level() {
+        for level in &[
+            AccessLevel::Read,
+            AccessLevel::ReadWrite,
+            AccessLevel::ReadWriteDirectMessages,
+        ] {
+            assert_eq!(level.to_string().parse::<AccessLevel>(), Ok(*level));
+        }
+        assert_eq!(
+            "read-write-directmessages".parse(),
+            Ok(AccessLevel::ReadWriteDirectMessages)
+        );
+        assert!("write".parse::<AccessLevel>().is_err());
+        assert!(!AccessLevel::Read.can_write());
+        assert!(AccessLevel::ReadWrite.can_write());
+        assert!(!AccessLevel::ReadWrite.can_access_direct_messages());
+        assert!(AccessLevel::ReadWriteDirectMessages.can_access_direct_messages());
+    }
+
+    #[test]
+    fn reverse_auth_parameters() {
+        let header = r#"OAuth oauth_nonce="L7GUlwUsp8Rd4YFfq9vnJXsTvX5UGo2ZhVpQbS6iKU", oauth_signature_method="HMAC-SHA1", oauth_timestamp="1330532342", oauth_consumer_key="JP3PyvG67rXRsnayOJOcQ", oauth_token="zTRH3kVIk7ixMkoGpTMdUv3L1tBCaTbf3n1MlJyTk", oauth_signature="Ldg9Wi%2FMiEyWkZP8sS5wE0GvQyA%3D", oauth_version="1.0""#;
+        let parameters: ReverseAuthParameters = header.parse().unwrap();
+        assert_eq!(parameters.as_str(), header);
+        assert_eq!(
+            parameters.oauth_consumer_key(),
+            Some("JP3PyvG67rXRsnayOJOcQ")
+        );
+        assert_eq!(
+            parameters.oauth_token(),
+            Some("zTRH3kVIk7ixMkoGpTMdUv3L1tBCaTbf3n1MlJyTk")
+        );
+        assert_eq!(
+            parameters.get("oauth_signature"),
+            Some("Ldg9Wi/MiEyWkZP8sS5wE0GvQyA=")
+        );
+
+        let json = serde_json::to_string(&parameters).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ReverseAuthParameters>(&json).unwrap(),
+            parameters
+        );
+
+        assert!("<errors><error code=\"87\">Client is not permitted to perform this action</error></errors>"
+            .parse::<ReverseAuthParameters>()
+            .is_err());
+    }
+
+    #[test]
+    fn bearer_token_response() {
+        let body = r#"{"token_type":"bearer","access_token":"AAAA%2FAAA%3DAAAAAAAA"}"#;
+        let response: BearerTokenResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(response.token_type, "bearer");
+        assert_eq!(
+            response.into_bearer_token(),
+            BearerToken::new("AAAA%2FAAA%3DAAAAAAAA".to_string())
+        );
+    }
+
+    #[test]
+    fn debug_redacts_secrets() {
+        let tokens = TokenKeys::new(KeyPair::new(
+            "ck".to_string(),
+            "consumer-secret".to_string(),
+        ))
+        .oauth_tokens(KeyPair::new("at".to_string(), "token-secret".to_string()));
+        let debug = format!("{:?}", tokens);
+        assert!(debug.contains("\"ck\"") && debug.contains("\"at\""));
+        assert!(!debug.contains("consumer-secret"));
+        assert!(!debug.contains("token-secret"));
+
+        let debug = format!("{:?}", BearerToken::new("AAAA%2FAAA".to_string()));
+        assert!(!debug.contains("AAAA"));
+
+        let client = OAuth2Client::new("client-id".to_string(), "http://127.0.0.1".to_string())
+            .client_secret("client-secret".to_string());
+        let debug = format!("{:?}", client);
+        assert!(debug.contains("client-id"));
+        assert!(!debug.contains("client-secret"));
+
+        let debug = format!("{:?}", CodeVerifier::from("pkce-verifier".to_string()));
+        assert!(!debug.contains("pkce-verifier"));
+    }
+
+    #[test]
+    fn token_keys_round_trip() {
+        let tokens = TokenKeys::new(KeyPair::new("ck".to_string(), "cs".to_string()))
+            .oauth_tokens(KeyPair::new("at".to_string(), "as".to_string()))
+            .user(12, "jack".to_string());
+        let json = serde_json::to_string(&tokens).unwrap();
+        assert_eq!(
+            json,
+            r#"{"consumer_keys":{"key":"ck","secret":"cs"},"oauth_tokens":{"key":"at","secret":"as"},"user_id":12,"screen_name":"jack"}"#
+        );
+        assert_eq!(serde_json::from_str::<TokenKeys>(&json).unwrap(), tokens);
+
+        let consumer_only = TokenKeys::new(KeyPair::new("ck".to_string(), "cs".to_string()));
+        let json = serde_json::to_string(&consumer_only).unwrap();
+        assert_eq!(
+            json,
+            r#"{"consumer_keys":{"key":"ck","secret":"cs"},"oauth_tokens":null}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<TokenKeys>(&json).unwrap(),
+            consumer_only
+        );
+    }
 }
@@ -0,0 +1,238 @@
+//! A loopback HTTP listener receiving the `oauth_callback` redirect of the
+//! 3-legged OAuth flow.
+//!
+//! The callback URL must be registered in the application settings, e.g.
+//! `http://127.0.0.1:8080/callback`.
+
+use super::{access_token, AccessTokenResponse, RequestTokenResponse};
+use crate::error::*;
+use crate::*;
+use actix_web::dev::Server;
+use actix_web::{web, App, HttpResponse, HttpServer};
+use futures_channel::oneshot;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::ToSocketAddrs;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const DEFAULT_SUCCESS_PAGE: &str = "<!DOCTYPE html>
+<html><body><p>Authorization complete. You can close this window.</p></body></html>";
+
+pub fn callback_server() -> CallbackServer {
+    CallbackServer::new()
+}
+
+pub struct CallbackServer {
+    path: String,
+    timeout: Option<Duration>,
+    success_page: String,
+}
+
+impl CallbackServer {
+    pub fn new() -> Self {
+        Self {
+            path: "/callback".to_string(),
+            timeout: None,
+            success_page: DEFAULT_SUCCESS_PAGE.to_string(),
+        }
+    }
+
+    /// Defaults to `/callback`.
+    pub fn path(mut self, path: String) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Gives up waiting for the redirect after `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// HTML shown in the browser once the redirect is received.
+    pub fn success_page(mut self, success_page: String) -> Self {
+        self.success_page = success_page;
+        self
+    }
+
+    /// Starts listening. Must be called within an actix system.
+    pub fn bind<A: ToSocketAddrs>(self, addr: A) -> std::io::Result<CallbackListener> {
+        let (sender, receiver) = oneshot::channel();
+        let pending = Arc::new(Mutex::new(Pending {
+            oauth_token: None,
+            sender: Some(sender),
+        }));
+        let success_page = self.success_page;
+        let path = self.path.clone();
+        let handler_pending = pending.clone();
+        let server = HttpServer::new(move || {
+            let pending = handler_pending.clone();
+            let success_page = success_page.clone();
+            App::new().route(
+                &path,
+                web::get().to(move |query: web::Query<HashMap<String, String>>| {
+                    let response = match pending.lock().unwrap().receive(query.into_inner()) {
+                        Ok(()) => HttpResponse::Ok()
+                            .content_type("text/html")
+                            .body(success_page.clone()),
+                        Err(error @ CallbackError::Denied) => HttpResponse::Ok()
+                            .content_type("text/html")
+                            .body(failure_page(error)),
+                        Err(error) => HttpResponse::BadRequest()
+                            .content_type("text/html")
+                            .body(failure_page(error)),
+                    };
+                    std::future::ready(response)
+                }),
+            )
+        })
+        .workers(1)
+        .disable_signals()
+        .bind(addr)?;
+
+        let addr = server.addrs()[0];
+        Ok(CallbackListener {
+            callback_url: format!("http://{}{}", addr, self.path),
+            server: server.run(),
+            pending,
+            receiver,
+            timeout: self.timeout,
+        })
+    }
+}
+
+impl Default for CallbackServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn failure_page(error: CallbackError) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html><body><p>Authorization failed: {}.</p></body></html>",
+        error
+    )
+}
+
+type Received = std::result::Result<Callback, CallbackError>;
+
+/// What the server is waiting for, shared with its handler.
+struct Pending {
+    oauth_token: Option<String>,
+    sender: Option<oneshot::Sender<Received>>,
+}
+
+impl Pending {
+    /// Ends the flow only for a redirect of the expected request token. Stray,
+    /// forged or incomplete requests get an error page and leave it waiting.
+    fn receive(
+        &mut self,
+        mut query: HashMap<String, String>,
+    ) -> std::result::Result<(), CallbackError> {
+        let expected = self.oauth_token.as_deref();
+        let received = if let Some(denied) = query.remove("denied") {
+            if Some(denied.as_str()) != expected {
+                return Err(CallbackError::TokenMismatch);
+            }
+            Err(CallbackError::Denied)
+        } else {
+            match (query.remove("oauth_token"), query.remove("oauth_verifier")) {
+                (Some(oauth_token), Some(_)) if Some(oauth_token.as_str()) != expected => {
+                    return Err(CallbackError::TokenMismatch)
+                }
+                (Some(oauth_token), Some(oauth_verifier)) => Ok(Callback {
+                    oauth_token,
+                    oauth_verifier,
+                }),
+                _ => return Err(CallbackError::MissingParameters),
+            }
+        };
+        let sender = self.sender.take().ok_or(CallbackError::Closed)?;
+        let _ = sender.send(received.clone());
+        received.map(|_| ())
+    }
+}
+
+pub struct CallbackListener {
+    callback_url: String,
+    server: Server,
+    pending: Arc<Mutex<Pending>>,
+    receiver: oneshot::Receiver<Received>,
+    timeout: Option<Duration>,
+}
+
+impl CallbackListener {
+    /// Pass this as `oauth_callback` to `oauth::request_token`.
+    pub fn callback_url(&self) -> &str {
+        &self.callback_url
+    }
+
+    /// Only accepts redirects for `request_token` from now on. Call it before
+    /// sending the user to the authorization page; `wait` calls it as well.
+    pub fn expect(&self, request_token: &RequestTokenResponse) {
+        self.pending.lock().unwrap().oauth_token = Some(request_token.oauth_token.clone());
+    }
+
+    /// Waits for the redirect belonging to `request_token`.
+    pub async fn wait(self, request_token: &RequestTokenResponse) -> Result<Callback> {
+        self.expect(request_token);
+        let received = match self.timeout {
+            Some(timeout) => actix_web::rt::time::timeout(timeout, self.receiver)
+                .await
+                .unwrap_or(Ok(Err(CallbackError::Timeout))),
+            None => self.receiver.await,
+        };
+        self.server.stop(false).await;
+        Ok(received.unwrap_or(Err(CallbackError::Closed))?)
+    }
+
+    /// Waits for the redirect and exchanges the verifier for an access token.
+    pub async fn access_token(
+        self,
+        consumer_keys: &KeyPair,
+        request_token: &RequestTokenResponse,
+    ) -> Result<AccessTokenResponse> {
+        let callback = self.wait(request_token).await?;
+        access_token(consumer_keys, callback.oauth_token, callback.oauth_verifier)
+            .send()
+            .await
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Callback {
+    pub oauth_token: String,
+    pub oauth_verifier: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CallbackError {
+    /// The user declined to authorize the application.
+    Denied,
+    /// A redirect without `oauth_token` or `oauth_verifier`. The server keeps
+    /// waiting.
+    MissingParameters,
+    /// A redirect for another request token. The server keeps waiting.
+    TokenMismatch,
+    Timeout,
+    Closed,
+}
+
+impl fmt::Display for CallbackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Denied => write!(f, "authorization was denied"),
+            Self::MissingParameters => {
+                write!(f, "callback is missing oauth_token or oauth_verifier")
+            }
+            Self::TokenMismatch => {
+                write!(f, "callback oauth_token does not match the request token")
+            }
+            Self::Timeout => write!(f, "timed out waiting for the callback"),
+            Self::Closed => write!(f, "callback server closed"),
+        }
+    }
+}
+
+impl std::error::Error for CallbackError {}
@@ -0,0 +1,489 @@
+//! OAuth 2.0 Authorization Code Flow with PKCE
+//!
+//! https://developer.twitter.com/en/docs/authentication/oauth-2-0/authorization-code
+
+use crate::error::*;
+use crate::utils::*;
+use crate::*;
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Client credentials and endpoints of an OAuth 2.0 application.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+pub struct OAuth2Client {
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: String,
+    authorize_url: String,
+    token_url: String,
+    revoke_url: String,
+}
+
+impl fmt::Debug for OAuth2Client {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OAuth2Client")
+            .field("client_id", &self.client_id)
+            .field(
+                "client_secret",
+                &self
+                    .client_secret
+                    .as_ref()
+                    .map(|_| format_args!("<redacted>")),
+            )
+            .field("redirect_uri", &self.redirect_uri)
+            .field("authorize_url", &self.authorize_url)
+            .field("token_url", &self.token_url)
+            .field("revoke_url", &self.revoke_url)
+            .finish()
+    }
+}
+
+impl OAuth2Client {
+    /// Creates a public client, which identifies itself by `client_id` only.
+    pub fn new(client_id: String, redirect_uri: String) -> Self {
+        Self {
+            client_id,
+            client_secret: None,
+            redirect_uri,
+            authorize_url: "https://twitter.com/i/oauth2/authorize".to_string(),
+            token_url: "https://api.twitter.com/2/oauth2/token".to_string(),
+            revoke_url: "https://api.twitter.com/2/oauth2/revoke".to_string(),
+        }
+    }
+
+    /// Makes this a confidential client, which authenticates with HTTP Basic.
+    pub fn client_secret(mut self, client_secret: String) -> Self {
+        self.client_secret = Some(client_secret);
+        self
+    }
+
+    pub fn authorize_url(mut self, authorize_url: String) -> Self {
+        self.authorize_url = authorize_url;
+        self
+    }
+
+    pub fn token_url(mut self, token_url: String) -> Self {
+        self.token_url = token_url;
+        self
+    }
+
+    pub fn revoke_url(mut self, revoke_url: String) -> Self {
+        self.revoke_url = revoke_url;
+        self
+    }
+
+    async fn post<T>(&self, url: &str, parameters: Vec<(&'static str, String)>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut request = Request::post(url);
+        request.form_body();
+        for (key, value) in parameters {
+            request.parameter(key, value);
+        }
+        let keys = self
+            .client_secret
+            .as_ref()
+            .map(|secret| KeyPair::new(self.client_id.clone(), secret.clone()));
+        let mut res = match &keys {
+            Some(keys) => request.send(Authorization::Basic(keys)),
+            None => {
+                request.parameter("client_id", &self.client_id);
+                request.send(Authorization::Unauthenticated)
+            }
+        }
+        .await?;
+        if res.status().is_success() {
+            Ok(res.json().await?)
+        } else {
+            Err(res.json::<OAuth2Error>().await?.into())
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scope {
+    TweetRead,
+    TweetWrite,
+    TweetModerateWrite,
+    UsersRead,
+    FollowsRead,
+    FollowsWrite,
+    OfflineAccess,
+    SpaceRead,
+    MuteRead,
+    MuteWrite,
+    LikeRead,
+    LikeWrite,
+    ListRead,
+    ListWrite,
+    BlockRead,
+    BlockWrite,
+    BookmarkRead,
+    BookmarkWrite,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TweetRead => write!(f, "tweet.read"),
+            Self::TweetWrite => write!(f, "tweet.write"),
+            Self::TweetModerateWrite => write!(f, "tweet.moderate.write"),
+            Self::UsersRead => write!(f, "users.read"),
+            Self::FollowsRead => write!(f, "follows.read"),
+            Self::FollowsWrite => write!(f, "follows.write"),
+            Self::OfflineAccess => write!(f, "offline.access"),
+            Self::SpaceRead => write!(f, "space.read"),
+            Self::MuteRead => write!(f, "mute.read"),
+            Self::MuteWrite => write!(f, "mute.write"),
+            Self::LikeRead => write!(f, "like.read"),
+            Self::LikeWrite => write!(f, "like.write"),
+            Self::ListRead => write!(f, "list.read"),
+            Self::ListWrite => write!(f, "list.write"),
+            Self::BlockRead => write!(f, "block.read"),
+            Self::BlockWrite => write!(f, "block.write"),
+            Self::BookmarkRead => write!(f, "bookmark.read"),
+            Self::BookmarkWrite => write!(f, "bookmark.write"),
+        }
+    }
+}
+
+/// A PKCE `code_verifier`, kept by the client until the code is exchanged.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+pub struct CodeVerifier(String);
+
+impl fmt::Debug for CodeVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("CodeVerifier")
+            .field(&format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+impl CodeVerifier {
+    pub fn new() -> Self {
+        Self(random_string(64))
+    }
+
+    pub fn secret(&self) -> &str {
+        &self.0
+    }
+
+    /// The `S256` code challenge: `BASE64URL(SHA256(code_verifier))`.
+    pub fn code_challenge(&self) -> String {
+        let digest = openssl::sha::sha256(self.0.as_bytes());
+        base64::encode_config(digest, base64::URL_SAFE_NO_PAD)
+    }
+}
+
+impl Default for CodeVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<String> for CodeVerifier {
+    fn from(code_verifier: String) -> Self {
+        Self(code_verifier)
+    }
+}
+
+pub fn authorize_oauth2<'a>(client: &'a OAuth2Client, scopes: &[Scope]) -> OAuth2Authorize<'a> {
+    OAuth2Authorize::new(client, scopes)
+}
+
+pub struct OAuth2Authorize<'a> {
+    client: &'a OAuth2Client,
+    scopes: Vec<Scope>,
+    state: Option<String>,
+    code_verifier: Option<CodeVerifier>,
+}
+
+impl<'a> OAuth2Authorize<'a> {
+    pub fn new(client: &'a OAuth2Client, scopes: &[Scope]) -> Self {
+        Self {
+            client,
+            scopes: scopes.to_vec(),
+            state: None,
+            code_verifier: None,
+        }
+    }
+
+    /// Defaults to a random string.
+    pub fn state(mut self, state: String) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Defaults to a random verifier.
+    pub fn code_verifier(mut self, code_verifier: CodeVerifier) -> Self {
+        self.code_verifier = Some(code_verifier);
+        self
+    }
+
+    pub fn into_request(self) -> OAuth2AuthorizationRequest {
+        let state = self.state.unwrap_or_else(|| random_string(32));
+        let code_verifier = self.code_verifier.unwrap_or_default();
+        let scope = self
+            .scopes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let url = format!(
+            "{}?response_type=code&client_id={}&redirect_uri={}&scope={}&state={}&code_challenge={}&code_challenge_method=S256",
+            self.client.authorize_url,
+            percent_encode(&self.client.client_id),
+            percent_encode(&self.client.redirect_uri),
+            percent_encode(&scope),
+            percent_encode(&state),
+            code_verifier.code_challenge(),
+        );
+        OAuth2AuthorizationRequest {
+            url,
+            state,
+            code_verifier,
+        }
+    }
+}
+
+/// What to send the user to, and what to keep until they come back.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct OAuth2AuthorizationRequest {
+    pub url: String,
+    pub state: String,
+    pub code_verifier: CodeVerifier,
+}
+
+impl OAuth2AuthorizationRequest {
+    /// Checks the `state` the callback was redirected with.
+    pub fn verify_state(&self, state: &str) -> bool {
+        self.state.len() == state.len()
+            && openssl::memcmp::eq(self.state.as_bytes(), state.as_bytes())
+    }
+
+    pub fn exchange<'a>(&'a self, client: &'a OAuth2Client, code: String) -> ExchangeCode<'a> {
+        ExchangeCode::new(client, code, &self.code_verifier)
+    }
+}
+
+pub fn exchange_code<'a>(
+    client: &'a OAuth2Client,
+    code: String,
+    code_verifier: &'a CodeVerifier,
+) -> ExchangeCode<'a> {
+    ExchangeCode::new(client, code, code_verifier)
+}
+
+/// `POST 2/oauth2/token` with `grant_type=authorization_code`
+pub struct ExchangeCode<'a> {
+    client: &'a OAuth2Client,
+    code: String,
+    code_verifier: &'a CodeVerifier,
+}
+
+impl<'a> ExchangeCode<'a> {
+    pub fn new(client: &'a OAuth2Client, code: String, code_verifier: &'a CodeVerifier) -> Self {
+        Self {
+            client,
+            code,
+            code_verifier,
+        }
+    }
+
+    pub async fn send(self) -> Result<OAuth2TokenResponse> {
+        let parameters = vec![
+            ("grant_type", "authorization_code".to_string()),
+            ("code", self.code),
+            ("redirect_uri", self.client.redirect_uri.clone()),
+            ("code_verifier", self.code_verifier.secret().to_string()),
+        ];
+        self.client.post(&self.client.token_url, parameters).await
+    }
+}
+
+pub fn refresh_oauth2_token<'a>(
+    client: &'a OAuth2Client,
+    token: &'a OAuth2Token,
+) -> RefreshOAuth2Token<'a> {
+    RefreshOAuth2Token::new(client, token)
+}
+
+/// `POST 2/oauth2/token` with `grant_type=refresh_token`
+///
+/// Refresh tokens are single-use: store the returned token in place of the old one.
+pub struct RefreshOAuth2Token<'a> {
+    client: &'a OAuth2Client,
+    token: &'a OAuth2Token,
+}
+
+impl<'a> RefreshOAuth2Token<'a> {
+    pub fn new(client: &'a OAuth2Client, token: &'a OAuth2Token) -> Self {
+        Self { client, token }
+    }
+
+    pub async fn send(self) -> Result<OAuth2TokenResponse> {
+        let refresh_token = self.token.refresh_token().ok_or_else(|| OAuth2Error {
+            error: "invalid_request".to_string(),
+            error_description: Some(
+                "the token has no refresh token; request the offline.access scope".to_string(),
+            ),
+        })?;
+        let parameters = vec![
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token.to_string()),
+        ];
+        self.client.post(&self.client.token_url, parameters).await
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenTypeHint {
+    AccessToken,
+    RefreshToken,
+}
+
+impl fmt::Display for TokenTypeHint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::AccessToken => write!(f, "access_token"),
+            Self::RefreshToken => write!(f, "refresh_token"),
+        }
+    }
+}
+
+pub fn revoke_oauth2_token(client: &OAuth2Client, token: String) -> RevokeOAuth2Token<'_> {
+    RevokeOAuth2Token::new(client, token)
+}
+
+/// `POST 2/oauth2/revoke`
+pub struct RevokeOAuth2Token<'a> {
+    client: &'a OAuth2Client,
+    token: String,
+    token_type_hint: Option<TokenTypeHint>,
+}
+
+impl<'a> RevokeOAuth2Token<'a> {
+    pub fn new(client: &'a OAuth2Client, token: String) -> Self {
+        Self {
+            client,
+            token,
+            token_type_hint: None,
+        }
+    }
+
+    pub fn token_type_hint(mut self, token_type_hint: TokenTypeHint) -> Self {
+        self.token_type_hint = Some(token_type_hint);
+        self
+    }
+
+    pub async fn send(self) -> Result<bool> {
+        let mut parameters = vec![("token", self.token)];
+        if let Some(token_type_hint) = self.token_type_hint {
+            parameters.push(("token_type_hint", token_type_hint.to_string()));
+        }
+        let response: RevokeResponse = self
+            .client
+            .post(&self.client.revoke_url, parameters)
+            .await?;
+        Ok(response.revoked)
+    }
+}
+
+#[derive(Deserialize)]
+struct RevokeResponse {
+    revoked: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct OAuth2TokenResponse {
+    pub token_type: String,
+    pub expires_in: Option<i64>,
+    pub access_token: String,
+    pub scope: Option<String>,
+    pub refresh_token: Option<String>,
+}
+
+impl OAuth2TokenResponse {
+    pub fn into_token(self) -> OAuth2Token {
+        OAuth2Token {
+            access_token: self.access_token,
+            refresh_token: self.refresh_token,
+            expires_at: self
+                .expires_in
+                .map(|expires_in| Utc::now() + Duration::seconds(expires_in)),
+            scopes: self
+                .scope
+                .map(|scope| scope.split_whitespace().map(ToString::to_string).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// An error response of the token or revocation endpoint.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct OAuth2Error {
+    pub error: String,
+    pub error_description: Option<String>,
+}
+
+impl fmt::Display for OAuth2Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.error_description {
+            Some(description) => write!(f, "{}: {}", self.error, description),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+impl std::error::Error for OAuth2Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_challenge() {
+        // RFC 7636 Appendix B
+        let verifier =
+            CodeVerifier::from("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string());
+        assert_eq!(
+            verifier.code_challenge(),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn authorize_url() {
+        let client = OAuth2Client::new(
+            "M1M5R3BMVy13QmpScXkzTUt5OE46MTpjaQ".to_string(),
+            "https://www.example.com".to_string(),
+        );
+        let request = authorize_oauth2(&client, &[Scope::TweetRead, Scope::UsersRead])
+            .state("state".to_string())
+            .code_verifier(CodeVerifier::from("challenge".to_string()))
+            .into_request();
+        assert_eq!(request.url, "https://twitter.com/i/oauth2/authorize?response_type=code&client_id=M1M5R3BMVy13QmpScXkzTUt5OE46MTpjaQ&redirect_uri=https%3A%2F%2Fwww.example.com&scope=tweet.read%20users.read&state=state&code_challenge=LdAL134CIs7YgmZUganB2fkHMJ0W4F7QB6HqY5KEd6k&code_challenge_method=S256");
+        assert!(request.verify_state("state"));
+        assert!(!request.verify_state("forged"));
+    }
+
+    #[test]
+    fn token_response() {
+        let body = r#"{"token_type":"bearer","expires_in":7200,"access_token":"bWRWa","scope":"tweet.read users.read offline.access","refresh_token":"bWRWb"}"#;
+        let response: OAuth2TokenResponse = serde_json::from_str(body).unwrap();
+        let token = response.into_token();
+        assert_eq!(token.access_token(), "bWRWa");
+        assert_eq!(token.refresh_token(), Some("bWRWb"));
+        assert_eq!(
+            token.scopes(),
+            &["tweet.read", "users.read", "offline.access"]
+        );
+        assert!(!token.is_expired());
+
+        let debug = format!("{:?}", token);
+        assert!(!debug.contains("bWRW"));
+        assert!(debug.contains("offline.access"));
+    }
+}
@@ -0,0 +1,353 @@
+//! Verifying incoming OAuth 1.0a signed requests, e.g. in a service accepting
+//! calls from this crate, or in a local stand-in for the Twitter API.
+
+use crate::utils::*;
+use crate::*;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Looks up the secrets a request was signed with.
+pub trait SecretStore {
+    fn consumer_secret(&self, consumer_key: &str) -> Option<String>;
+    fn token_secret(&self, consumer_key: &str, oauth_token: &str) -> Option<String>;
+}
+
+/// Accepts requests signed with these credentials only.
+impl SecretStore for TokenKeys {
+    fn consumer_secret(&self, consumer_key: &str) -> Option<String> {
+        if self.consumer_keys.key == consumer_key {
+            Some(self.consumer_keys.secret.clone())
+        } else {
+            None
+        }
+    }
+
+    fn token_secret(&self, consumer_key: &str, oauth_token: &str) -> Option<String> {
+        let oauth_tokens = self.oauth_tokens.as_ref()?;
+        if self.consumer_keys.key == consumer_key && oauth_tokens.key == oauth_token {
+            Some(oauth_tokens.secret.clone())
+        } else {
+            None
+        }
+    }
+}
+
+impl SecretStore for Vec<TokenKeys> {
+    fn consumer_secret(&self, consumer_key: &str) -> Option<String> {
+        self.iter()
+            .find_map(|tokens| tokens.consumer_secret(consumer_key))
+    }
+
+    fn token_secret(&self, consumer_key: &str, oauth_token: &str) -> Option<String> {
+        self.iter()
+            .find_map(|tokens| tokens.token_secret(consumer_key, oauth_token))
+    }
+}
+
+/// Checks `Authorization: OAuth ...` headers of incoming requests.
+///
+/// Only `HMAC-SHA1` signatures are accepted. Timestamps must lie within
+/// `window` of the current time, and each nonce is accepted once per
+/// consumer key and timestamp.
+pub struct RequestVerifier<S> {
+    secrets: S,
+    window: i64,
+    nonces: Mutex<HashSet<(String, String, i64)>>,
+}
+
+impl<S: SecretStore> RequestVerifier<S> {
+    pub fn new(secrets: S) -> Self {
+        Self {
+            secrets,
+            window: 300,
+            nonces: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Allowed clock skew in seconds. Defaults to 300.
+    pub fn window(mut self, seconds: i64) -> Self {
+        self.window = seconds;
+        self
+    }
+
+    /// `url` is the full request URL including the query string, and `body`
+    /// the `application/x-www-form-urlencoded` body, if any.
+    pub fn verify(
+        &self,
+        method: &str,
+        url: &str,
+        authorization: &str,
+        body: Option<&str>,
+    ) -> std::result::Result<VerifiedRequest, VerifyError> {
+        self.verify_at(method, url, authorization, body, Utc::now())
+    }
+
+    pub fn verify_at(
+        &self,
+        method: &str,
+        url: &str,
+        authorization: &str,
+        body: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> std::result::Result<VerifiedRequest, VerifyError> {
+        let oauth_params = parse_oauth_header(authorization).ok_or(VerifyError::MalformedHeader)?;
+        let get = |key: &str| {
+            oauth_params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.as_str())
+        };
+        let required = |key: &'static str| get(key).ok_or(VerifyError::MissingParameter(key));
+
+        let consumer_key = required("oauth_consumer_key")?;
+        let signature = required("oauth_signature")?;
+        let nonce = required("oauth_nonce")?;
+        let timestamp = required("oauth_timestamp")?;
+        let signature_method = required("oauth_signature_method")?;
+        let oauth_token = get("oauth_token");
+        if signature_method != "HMAC-SHA1" {
+            return Err(VerifyError::UnsupportedSignatureMethod(
+                signature_method.to_string(),
+            ));
+        }
+        if get("oauth_version").is_some_and(|version| version != "1.0") {
+            return Err(VerifyError::UnsupportedVersion);
+        }
+
+        let timestamp: i64 = timestamp.parse().map_err(|_| VerifyError::StaleTimestamp)?;
+        if (now.timestamp() - timestamp).abs() > self.window {
+            return Err(VerifyError::StaleTimestamp);
+        }
+
+        let consumer_secret = self
+            .secrets
+            .consumer_secret(consumer_key)
+            .ok_or(VerifyError::UnknownConsumer)?;
+        let mut tokens = TokenKeys::new(KeyPair::new(consumer_key.to_string(), consumer_secret));
+        if let Some(oauth_token) = oauth_token {
+            let token_secret = self
+                .secrets
+                .token_secret(consumer_key, oauth_token)
+                .ok_or(VerifyError::UnknownToken)?;
+            tokens = tokens.oauth_tokens(KeyPair::new(oauth_token.to_string(), token_secret));
+        }
+
+        let (base_url, query) = match url.split_once('?') {
+            Some((base_url, query)) => (base_url, Some(query)),
+            None => (url, None),
+        };
+        let mut params = Vec::new();
+        for (key, value) in &oauth_params {
+            if key != "oauth_signature" && key != "realm" {
+                params.push((percent_encode(key), percent_encode(value)));
+            }
+        }
+        for pairs in query.iter().chain(body.iter()) {
+            for (key, value) in parse_form(pairs).ok_or(VerifyError::MalformedParameters)? {
+                params.push((percent_encode(&key), percent_encode(&value)));
+            }
+        }
+        let signature_base_string = signature_base_string(
+            &method.to_uppercase(),
+            &normalize_base_url(base_url),
+            params,
+        );
+
+        let expected = sign(&tokens, &signature_base_string);
+        let signature = base64::decode(signature).map_err(|_| VerifyError::InvalidSignature)?;
+        if signature.len() != expected.len() || !openssl::memcmp::eq(&signature, &expected) {
+            return Err(VerifyError::InvalidSignature);
+        }
+
+        let mut nonces = self.nonces.lock().unwrap();
+        let window = self.window;
+        nonces.retain(|(_, _, seen)| (now.timestamp() - *seen).abs() <= window);
+        let key = (consumer_key.to_string(), nonce.to_string(), timestamp);
+        if !nonces.insert(key) {
+            return Err(VerifyError::ReplayedNonce);
+        }
+
+        Ok(VerifiedRequest {
+            consumer_key: consumer_key.to_string(),
+            oauth_token: oauth_token.map(ToString::to_string),
+            oauth_params,
+        })
+    }
+}
+
+fn parse_form(input: &str) -> Option<Vec<(String, String)>> {
+    input
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Some((
+                percent_decode(&key.replace('+', " "))?,
+                percent_decode(&value.replace('+', " "))?,
+            ))
+        })
+        .collect()
+}
+
+/// Lowercases the scheme and host and drops the default port.
+fn normalize_base_url(url: &str) -> String {
+    let (scheme, rest) = match url.split_once("://") {
+        Some(split) => split,
+        None => return url.to_string(),
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    let scheme = scheme.to_lowercase();
+    let mut authority = authority.to_lowercase();
+    let default_port = match scheme.as_str() {
+        "http" => ":80",
+        "https" => ":443",
+        _ => "",
+    };
+    if !default_port.is_empty() && authority.ends_with(default_port) {
+        authority.truncate(authority.len() - default_port.len());
+    }
+    format!("{}://{}{}", scheme, authority, path)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedRequest {
+    pub consumer_key: String,
+    pub oauth_token: Option<String>,
+    /// Decoded `oauth_*` parameters of the header.
+    pub oauth_params: Vec<(String, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VerifyError {
+    MalformedHeader,
+    MalformedParameters,
+    MissingParameter(&'static str),
+    UnsupportedSignatureMethod(String),
+    UnsupportedVersion,
+    StaleTimestamp,
+    UnknownConsumer,
+    UnknownToken,
+    InvalidSignature,
+    ReplayedNonce,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MalformedHeader => write!(f, "malformed OAuth Authorization header"),
+            Self::MalformedParameters => write!(f, "malformed request parameters"),
+            Self::MissingParameter(key) => write!(f, "missing {}", key),
+            Self::UnsupportedSignatureMethod(method) => {
+                write!(f, "unsupported signature method: {}", method)
+            }
+            Self::UnsupportedVersion => write!(f, "unsupported oauth_version"),
+            Self::StaleTimestamp => write!(f, "timestamp out of bounds"),
+            Self::UnknownConsumer => write!(f, "unknown consumer key"),
+            Self::UnknownToken => write!(f, "unknown or expired token"),
+            Self::InvalidSignature => write!(f, "invalid signature"),
+            Self::ReplayedNonce => write!(f, "nonce already used"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn get_tokens() -> TokenKeys {
+        TokenKeys::new(KeyPair::new(
+            "xvz1evFS4wEEPTGEFPHBog".to_string(),
+            "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw".to_string(),
+        ))
+        .oauth_tokens(KeyPair::new(
+            "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb".to_string(),
+            "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE".to_string(),
+        ))
+    }
+
+    const URL: &str = "https://api.twitter.com/1.1/statuses/update.json?include_entities=true";
+    const BODY: &str =
+        "status=Hello%20Ladies%20%2b%20Gentlemen%2c%20a%20signed%20OAuth%20request%21";
+
+    fn header(signature: &str) -> String {
+        format!(
+            r#"OAuth oauth_consumer_key="xvz1evFS4wEEPTGEFPHBog", oauth_nonce="kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg", oauth_signature="{}", oauth_signature_method="HMAC-SHA1", oauth_timestamp="1318622958", oauth_token="370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb", oauth_version="1.0""#,
+            signature
+        )
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.timestamp_opt(1318622958 + 30, 0).unwrap()
+    }
+
+    #[test]
+    fn verify() {
+        let verifier = RequestVerifier::new(get_tokens());
+        let header = header("hCtSmYh%2BiHYCEqBWrE7C7hYmtUk%3D");
+        let verified = verifier
+            .verify_at("POST", URL, &header, Some(BODY), now())
+            .unwrap();
+        assert_eq!(verified.consumer_key, "xvz1evFS4wEEPTGEFPHBog");
+        assert_eq!(
+            verified.oauth_token.as_deref(),
+            Some("370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb")
+        );
+
+        assert_eq!(
+            verifier.verify_at("POST", URL, &header, Some(BODY), now()),
+            Err(VerifyError::ReplayedNonce)
+        );
+    }
+
+    #[test]
+    fn reject() {
+        let verifier = RequestVerifier::new(get_tokens());
+        let header = header("hCtSmYh%2BiHYCEqBWrE7C7hYmtUk%3D");
+        assert_eq!(
+            verifier.verify_at("POST", URL, &header, Some("status=Tampered"), now()),
+            Err(VerifyError::InvalidSignature)
+        );
+        assert_eq!(
+            verifier.verify_at("GET", URL, &header, Some(BODY), now()),
+            Err(VerifyError::InvalidSignature)
+        );
+        let late = Utc.timestamp_opt(1318622958 + 301, 0).unwrap();
+        assert_eq!(
+            verifier.verify_at("POST", URL, &header, Some(BODY), late),
+            Err(VerifyError::StaleTimestamp)
+        );
+        assert_eq!(
+            verifier.verify_at("POST", URL, "Bearer AAAA", Some(BODY), now()),
+            Err(VerifyError::MalformedHeader)
+        );
+
+        let other = TokenKeys::new(KeyPair::new(
+            "xvz1evFS4wEEPTGEFPHBog".to_string(),
+            "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw".to_string(),
+        ));
+        assert_eq!(
+            RequestVerifier::new(other).verify_at("POST", URL, &header, Some(BODY), now()),
+            Err(VerifyError::UnknownToken)
+        );
+    }
+
+    #[test]
+    fn verify_own_signature() {
+        let tokens = get_tokens();
+        let mut request = Request::get("https://api.twitter.com/1.1/statuses/show.json");
+        request.query("id", 210462857140252672u64);
+        let header = request.get_oauth_authorization_header(&tokens);
+
+        let verifier = RequestVerifier::new(vec![tokens]);
+        let url = "HTTPS://API.Twitter.com:443/1.1/statuses/show.json?id=210462857140252672";
+        assert!(verifier.verify("GET", url, &header, None).is_ok());
+    }
+}
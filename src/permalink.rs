@@ -0,0 +1,349 @@
+//! Tweet and profile links as users paste them, e.g.
+//! `https://twitter.com/TwitterDev/status/1050118621198921728`.
+
+use crate::{TweetId, UserId};
+use std::fmt;
+use std::str::FromStr;
+
+const HOSTS: &[&str] = &[
+    "twitter.com",
+    "www.twitter.com",
+    "mobile.twitter.com",
+    "m.twitter.com",
+    "x.com",
+    "www.x.com",
+    "mobile.x.com",
+];
+
+/// First path segments that are pages rather than screen names.
+const RESERVED: &[&str] = &[
+    "explore",
+    "hashtag",
+    "home",
+    "i",
+    "intent",
+    "login",
+    "logout",
+    "messages",
+    "notifications",
+    "privacy",
+    "search",
+    "settings",
+    "share",
+    "signup",
+    "tos",
+];
+
+/// Profile tabs that still point at the profile.
+const PROFILE_TABS: &[&str] = &["with_replies", "media", "likes", "followers", "following"];
+
+/// A link to a Tweet. `/i/web/status/:id` links carry no screen name.
+///
+/// Builders taking `impl Into<TweetId>` accept it directly.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TweetLink {
+    pub screen_name: Option<String>,
+    pub id: TweetId,
+}
+
+/// A link to a profile, by screen name or, for `/i/user/:id`, by id.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UserLink {
+    Id(UserId),
+    ScreenName(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Permalink {
+    Tweet(TweetLink),
+    User(UserLink),
+}
+
+#[derive(Debug)]
+pub struct InvalidPermalink {
+    pub url: String,
+}
+
+impl fmt::Display for InvalidPermalink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "not a Tweet or profile link: {:?}", self.url)
+    }
+}
+
+impl std::error::Error for InvalidPermalink {}
+
+fn is_screen_name(s: &str) -> bool {
+    (1..=20).contains(&s.len()) && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+fn parse(url: &str) -> Option<Permalink> {
+    let url = url.trim();
+    let rest = ["https://", "http://"]
+        .iter()
+        .find(|scheme| {
+            url.get(..scheme.len())
+                .is_some_and(|s| s.eq_ignore_ascii_case(scheme))
+        })
+        .map_or(url, |scheme| &url[scheme.len()..]);
+    let (host, rest) = rest.split_at(rest.find(['/', '?', '#']).unwrap_or(rest.len()));
+    let host = match host.split_once(':') {
+        Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => host,
+        Some(_) => return None,
+        None => host,
+    };
+    if !HOSTS.iter().any(|h| h.eq_ignore_ascii_case(host)) {
+        return None;
+    }
+    let (path, fragment) = match rest.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (rest, None),
+    };
+    // Old `/#!/:screen_name/status/:id` links.
+    let path = match fragment.and_then(|f| f.strip_prefix('!')) {
+        Some(hashbang) if path.trim_matches('/').is_empty() => hashbang,
+        _ => path,
+    };
+    let path = path.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let tweet_id = |id: &str| id.parse().ok();
+    match segments.as_slice() {
+        ["i", "web", "status", id, ..] | ["i", "status", id, ..] => {
+            Some(Permalink::Tweet(TweetLink {
+                screen_name: None,
+                id: tweet_id(id)?,
+            }))
+        }
+        ["i", "user", id] => Some(Permalink::User(UserLink::Id(id.parse().ok()?))),
+        [name, "status", id, ..] | [name, "statuses", id, ..]
+            if is_screen_name(name) && !RESERVED.contains(name) =>
+        {
+            Some(Permalink::Tweet(TweetLink {
+                screen_name: Some(name.to_string()),
+                id: tweet_id(id)?,
+            }))
+        }
+        [name] | [name, _] if is_screen_name(name) && !RESERVED.contains(name) => {
+            if segments.len() == 2 && !PROFILE_TABS.contains(&segments[1]) {
+                return None;
+            }
+            Some(Permalink::User(UserLink::ScreenName(name.to_string())))
+        }
+        _ => None,
+    }
+}
+
+impl FromStr for Permalink {
+    type Err = InvalidPermalink;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s).ok_or_else(|| InvalidPermalink { url: s.to_string() })
+    }
+}
+
+impl FromStr for TweetLink {
+    type Err = InvalidPermalink;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse(s) {
+            Some(Permalink::Tweet(link)) => Ok(link),
+            _ => Err(InvalidPermalink { url: s.to_string() }),
+        }
+    }
+}
+
+impl FromStr for UserLink {
+    type Err = InvalidPermalink;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse(s) {
+            Some(Permalink::User(link)) => Ok(link),
+            _ => Err(InvalidPermalink { url: s.to_string() }),
+        }
+    }
+}
+
+impl From<TweetLink> for TweetId {
+    fn from(link: TweetLink) -> Self {
+        link.id
+    }
+}
+
+impl From<&TweetLink> for TweetId {
+    fn from(link: &TweetLink) -> Self {
+        link.id
+    }
+}
+
+impl fmt::Display for TweetLink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.screen_name {
+            Some(screen_name) => {
+                write!(f, "https://twitter.com/{}/status/{}", screen_name, self.id)
+            }
+            None => write!(f, "https://twitter.com/i/web/status/{}", self.id),
+        }
+    }
+}
+
+impl fmt::Display for UserLink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "https://twitter.com/i/user/{}", id),
+            Self::ScreenName(screen_name) => write!(f, "https://twitter.com/{}", screen_name),
+        }
+    }
+}
+
+impl fmt::Display for Permalink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Tweet(link) => write!(f, "{}", link),
+            Self::User(link) => write!(f, "{}", link),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tweet(screen_name: Option<&str>, id: u64) -> Permalink {
+        Permalink::Tweet(TweetLink {
+            screen_name: screen_name.map(str::to_string),
+            id: TweetId(id),
+        })
+    }
+
+    fn user(screen_name: &str) -> Permalink {
+        Permalink::User(UserLink::ScreenName(screen_name.to_string()))
+    }
+
+    fn parse(url: &str) -> Option<Permalink> {
+        url.parse().ok()
+    }
+
+    #[test]
+    fn tweets() {
+        let id = 1050118621198921728;
+        let expected = Some(tweet(Some("TwitterDev"), id));
+        for url in &[
+            "https://twitter.com/TwitterDev/status/1050118621198921728",
+            "http://www.twitter.com/TwitterDev/status/1050118621198921728/",
+            "https://mobile.twitter.com/TwitterDev/status/1050118621198921728?s=20",
+            "HTTPS://X.com/TwitterDev/status/1050118621198921728",
+            "x.com/TwitterDev/statuses/1050118621198921728",
+            "https://twitter.com/TwitterDev/status/1050118621198921728/photo/1",
+            "https://twitter.com/#!/TwitterDev/status/1050118621198921728",
+            "https://twitter.com:443/TwitterDev/status/1050118621198921728",
+        ] {
+            assert_eq!(parse(url), expected, "{}", url);
+        }
+        assert_eq!(
+            parse("https://twitter.com/i/web/status/1050118621198921728"),
+            Some(tweet(None, id))
+        );
+        assert_eq!(
+            parse("https://x.com/i/status/1050118621198921728"),
+            Some(tweet(None, id))
+        );
+    }
+
+    #[test]
+    fn users() {
+        assert_eq!(
+            parse("https://twitter.com/TwitterDev"),
+            Some(user("TwitterDev"))
+        );
+        assert_eq!(parse("x.com/TwitterDev/"), Some(user("TwitterDev")));
+        assert_eq!(
+            parse("https://mobile.twitter.com/TwitterDev/with_replies"),
+            Some(user("TwitterDev"))
+        );
+        assert_eq!(
+            parse("https://twitter.com/i/user/2244994945"),
+            Some(Permalink::User(UserLink::Id(UserId(2244994945))))
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for url in &[
+            "https://example.com/TwitterDev/status/1050118621198921728",
+            "https://twitter.com.example.com/TwitterDev",
+            "https://twitter.com/TwitterDev/status/abc",
+            "https://twitter.com/TwitterDev/lists",
+            "https://twitter.com/home",
+            "https://twitter.com/search?q=rust",
+            "https://twitter.com/",
+            "https://twitter.com/not-a-name",
+            "https://twitter.com/settings/status/1",
+            "https://twitter.com:/TwitterDev",
+            "https://twitter.com:https/TwitterDev",
+        ] {
+            assert_eq!(parse(url), None, "{}", url);
+        }
+        assert!("https://twitter.com/TwitterDev"
+            .parse::<TweetLink>()
+            .is_err());
+    }
+
+    #[test]
+    fn display() {
+        for url in &[
+            "https://twitter.com/TwitterDev/status/1050118621198921728",
+            "https://twitter.com/i/web/status/1050118621198921728",
+            "https://twitter.com/TwitterDev",
+            "https://twitter.com/i/user/2244994945",
+        ] {
+            assert_eq!(url.parse::<Permalink>().unwrap().to_string(), *url);
+        }
+    }
+
+    #[test]
+    fn into_tweet_id() {
+        let link: TweetLink = "https://x.com/TwitterDev/status/1050118621198921728"
+            .parse()
+            .unwrap();
+        assert_eq!(TweetId::from(&link), 1050118621198921728);
+        assert_eq!(TweetId::from(link), 1050118621198921728);
+    }
+
+    #[actix_rt::test]
+    async fn into_oembed_url() {
+        let srv = actix_web::test::start(|| {
+            actix_web::App::new().route(
+                "/oembed",
+                actix_web::web::get().to(
+                    |query: actix_web::web::Query<std::collections::HashMap<String, String>>| {
+                        std::future::ready(actix_web::HttpResponse::Ok().json(serde_json::json!({
+                            "url": query["url"],
+                            "author_name": "Twitter Dev",
+                            "author_url": "https://twitter.com/TwitterDev",
+                            "html": "<blockquote class=\"twitter-tweet\"></blockquote>",
+                            "width": 550,
+                            "height": null,
+                            "type": "rich",
+                            "cache_age": "3153600000",
+                            "provider_name": "Twitter",
+                            "provider_url": "https://twitter.com",
+                            "version": "1.0"
+                        })))
+                    },
+                ),
+            )
+        });
+        let link: TweetLink = "https://x.com/TwitterDev/status/1050118621198921728"
+            .parse()
+            .unwrap();
+        let token = crate::BearerToken::new("AAAA".to_string());
+        let oembed = crate::statuses::oembed(&token, link)
+            .send_to(&srv.url("/oembed"))
+            .await
+            .unwrap();
+        assert_eq!(
+            oembed.url,
+            "https://twitter.com/TwitterDev/status/1050118621198921728"
+        );
+    }
+}
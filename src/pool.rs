@@ -0,0 +1,409 @@
+//! Spreading requests over many authorized accounts.
+
+use crate::error::*;
+use crate::*;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+
+/// Tokens of many accounts, each with its own rate limit budget per endpoint.
+///
+/// Budgets are counted locally over 15-minute windows. `report` corrects them
+/// from the `x-rate-limit-*` headers of error responses, and
+/// `run_with_rate_limit` or `record` from those of successful ones. Accounts
+/// whose tokens are rejected with error 89 are dropped and handed to the
+/// `on_invalid` hook.
+pub struct TokenPool {
+    accounts: Vec<Account>,
+    limits: HashMap<String, u32>,
+    default_limit: u32,
+    window: Duration,
+    on_invalid: Option<InvalidHook>,
+}
+
+type InvalidHook = Box<dyn FnMut(&TokenKeys) + Send>;
+
+struct Account {
+    tokens: TokenKeys,
+    budgets: HashMap<String, Budget>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Budget {
+    remaining: u32,
+    reset: DateTime<Utc>,
+}
+
+impl TokenPool {
+    pub fn new() -> Self {
+        Self {
+            accounts: Vec::new(),
+            limits: HashMap::new(),
+            default_limit: 15,
+            window: Duration::minutes(15),
+            on_invalid: None,
+        }
+    }
+
+    /// Requests per window and account for `endpoint`, e.g. `statuses/show/:id`.
+    pub fn limit(mut self, endpoint: &str, limit: u32) -> Self {
+        self.limits.insert(endpoint.to_string(), limit);
+        self
+    }
+
+    /// Used for endpoints without a `limit`. Defaults to 15.
+    pub fn default_limit(mut self, limit: u32) -> Self {
+        self.default_limit = limit;
+        self
+    }
+
+    /// Called with the tokens of each dropped account, e.g. to start re-authorization.
+    pub fn on_invalid<F: FnMut(&TokenKeys) + Send + 'static>(mut self, on_invalid: F) -> Self {
+        self.on_invalid = Some(Box::new(on_invalid));
+        self
+    }
+
+    pub fn add(&mut self, tokens: TokenKeys) {
+        self.remove(&tokens);
+        self.accounts.push(Account {
+            tokens,
+            budgets: HashMap::new(),
+        });
+    }
+
+    pub fn remove(&mut self, tokens: &TokenKeys) -> bool {
+        let len = self.accounts.len();
+        self.accounts.retain(|account| account.tokens != *tokens);
+        self.accounts.len() != len
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    pub fn tokens(&self) -> impl Iterator<Item = &TokenKeys> {
+        self.accounts.iter().map(|account| &account.tokens)
+    }
+
+    /// Requests left for `tokens` on `endpoint` in the current window.
+    pub fn remaining(&self, tokens: &TokenKeys, endpoint: &str) -> Option<u32> {
+        let now = Utc::now();
+        let account = self.accounts.iter().find(|a| a.tokens == *tokens)?;
+        Some(self.budget(account, endpoint, now).remaining)
+    }
+
+    /// The account with the most remaining budget for `endpoint`.
+    pub fn pick(&self, endpoint: &str) -> Option<&TokenKeys> {
+        let now = Utc::now();
+        self.pick_index(endpoint, now)
+            .map(|index| &self.accounts[index].tokens)
+    }
+
+    /// Picks an account and spends one request of its budget.
+    pub fn acquire(&mut self, endpoint: &str) -> Result<TokenKeys> {
+        let now = Utc::now();
+        let index = self
+            .pick_index(endpoint, now)
+            .ok_or_else(|| self.exhausted(endpoint, now))?;
+        let mut budget = self.budget(&self.accounts[index], endpoint, now);
+        budget.remaining -= 1;
+        let account = &mut self.accounts[index];
+        account.budgets.insert(endpoint.to_string(), budget);
+        Ok(account.tokens.clone())
+    }
+
+    /// Updates the budget of `tokens` from the outcome of a request.
+    pub fn report<T>(&mut self, tokens: &TokenKeys, endpoint: &str, result: &Result<T>) {
+        let error = match result {
+            Err(Error::TwitterError(error)) => error,
+            _ => return,
+        };
+        if error.has_code(TwitterError::INVALID_OR_EXPIRED_TOKEN) {
+            self.invalidate(tokens);
+        } else if error.has_code(TwitterError::RATE_LIMIT_EXCEEDED) || error.status == 429 {
+            let reset = error
+                .rate_limit
+                .map(|rate_limit| rate_limit.reset)
+                .unwrap_or_else(|| Utc::now() + self.window);
+            self.set_budget(
+                tokens,
+                endpoint,
+                Budget {
+                    remaining: 0,
+                    reset,
+                },
+            );
+        } else if let Some(rate_limit) = error.rate_limit {
+            self.record(tokens, endpoint, rate_limit);
+        }
+    }
+
+    /// Overrides the local count with `x-rate-limit-*` headers.
+    pub fn record(&mut self, tokens: &TokenKeys, endpoint: &str, rate_limit: RateLimit) {
+        let budget = Budget {
+            remaining: rate_limit.remaining,
+            reset: rate_limit.reset,
+        };
+        self.set_budget(tokens, endpoint, budget);
+    }
+
+    /// Drops the account and hands its tokens to the `on_invalid` hook.
+    pub fn invalidate(&mut self, tokens: &TokenKeys) {
+        if self.remove(tokens) {
+            if let Some(on_invalid) = &mut self.on_invalid {
+                on_invalid(tokens);
+            }
+        }
+    }
+
+    /// Runs `f` with the account picked for `endpoint`.
+    ///
+    /// ```no_run
+    /// # use alpheidae::*;
+    /// # async fn example(pool: &mut pool::TokenPool) -> Result<Tweet, Error> {
+    /// let id = 210462857140252672;
+    /// pool.run("statuses/show/:id", |tokens| async move {
+    ///     statuses::show(&tokens, id).send().await
+    /// })
+    /// .await
+    /// # }
+    /// ```
+    pub async fn run<T, F, Fut>(&mut self, endpoint: &str, f: F) -> Result<T>
+    where
+        F: FnOnce(TokenKeys) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let tokens = self.acquire(endpoint)?;
+        let result = f(tokens.clone()).await;
+        self.report(&tokens, endpoint, &result);
+        result
+    }
+
+    /// Like `run`, for builders' `send_with_rate_limit`, so the budget follows
+    /// the headers of successful responses too.
+    ///
+    /// ```no_run
+    /// # use alpheidae::*;
+    /// # async fn example(pool: &mut pool::TokenPool) -> Result<Tweet, Error> {
+    /// let id = 210462857140252672;
+    /// pool.run_with_rate_limit("statuses/show/:id", |tokens| async move {
+    ///     statuses::show(&tokens, id).send_with_rate_limit().await
+    /// })
+    /// .await
+    /// # }
+    /// ```
+    pub async fn run_with_rate_limit<T, F, Fut>(&mut self, endpoint: &str, f: F) -> Result<T>
+    where
+        F: FnOnce(TokenKeys) -> Fut,
+        Fut: Future<Output = Result<(T, Option<RateLimit>)>>,
+    {
+        let tokens = self.acquire(endpoint)?;
+        let result = f(tokens.clone()).await;
+        self.report(&tokens, endpoint, &result);
+        let (value, rate_limit) = result?;
+        if let Some(rate_limit) = rate_limit {
+            self.record(&tokens, endpoint, rate_limit);
+        }
+        Ok(value)
+    }
+
+    fn budget(&self, account: &Account, endpoint: &str, now: DateTime<Utc>) -> Budget {
+        match account.budgets.get(endpoint) {
+            Some(budget) if budget.reset > now => *budget,
+            _ => Budget {
+                remaining: self
+                    .limits
+                    .get(endpoint)
+                    .copied()
+                    .unwrap_or(self.default_limit),
+                reset: now + self.window,
+            },
+        }
+    }
+
+    fn set_budget(&mut self, tokens: &TokenKeys, endpoint: &str, budget: Budget) {
+        if let Some(account) = self.accounts.iter_mut().find(|a| a.tokens == *tokens) {
+            account.budgets.insert(endpoint.to_string(), budget);
+        }
+    }
+
+    fn pick_index(&self, endpoint: &str, now: DateTime<Utc>) -> Option<usize> {
+        self.accounts
+            .iter()
+            .map(|account| self.budget(account, endpoint, now).remaining)
+            .enumerate()
+            .filter(|(_, remaining)| *remaining > 0)
+            .max_by_key(|(index, remaining)| (*remaining, std::cmp::Reverse(*index)))
+            .map(|(index, _)| index)
+    }
+
+    fn exhausted(&self, endpoint: &str, now: DateTime<Utc>) -> PoolError {
+        if self.accounts.is_empty() {
+            return PoolError::Empty;
+        }
+        PoolError::Exhausted {
+            endpoint: endpoint.to_string(),
+            reset: self
+                .accounts
+                .iter()
+                .map(|account| self.budget(account, endpoint, now).reset)
+                .min(),
+        }
+    }
+}
+
+impl Default for TokenPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PoolError {
+    Empty,
+    /// Every account has spent its budget for the endpoint until `reset`.
+    Exhausted {
+        endpoint: String,
+        reset: Option<DateTime<Utc>>,
+    },
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "token pool is empty"),
+            Self::Exhausted {
+                endpoint,
+                reset: Some(reset),
+            } => write!(f, "rate limit of {} exhausted until {}", endpoint, reset),
+            Self::Exhausted { endpoint, .. } => {
+                write!(f, "rate limit of {} exhausted", endpoint)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn tokens(user_id: u64) -> TokenKeys {
+        TokenKeys::new(KeyPair::new("ck".to_string(), "cs".to_string()))
+            .oauth_tokens(KeyPair::new(format!("{}-at", user_id), "as".to_string()))
+            .user(user_id, format!("user{}", user_id))
+    }
+
+    fn twitter_error(code: u32) -> Error {
+        TwitterError {
+            status: 401,
+            rate_limit: None,
+            errors: vec![ErrorMessage {
+                code,
+                message: "Invalid or expired token.".to_string(),
+            }],
+        }
+        .into()
+    }
+
+    #[test]
+    fn pick_most_remaining() {
+        let mut pool = TokenPool::new().limit("statuses/show/:id", 2);
+        pool.add(tokens(1));
+        pool.add(tokens(2));
+
+        assert_eq!(pool.acquire("statuses/show/:id").unwrap(), tokens(1));
+        assert_eq!(pool.pick("statuses/show/:id"), Some(&tokens(2)));
+        assert_eq!(pool.acquire("statuses/show/:id").unwrap(), tokens(2));
+        assert_eq!(pool.acquire("statuses/show/:id").unwrap(), tokens(1));
+        assert_eq!(pool.remaining(&tokens(1), "statuses/show/:id"), Some(0));
+        assert_eq!(pool.remaining(&tokens(1), "statuses/update"), Some(15));
+
+        assert_eq!(pool.acquire("statuses/show/:id").unwrap(), tokens(2));
+        match pool.acquire("statuses/show/:id") {
+            Err(Error::PoolError(PoolError::Exhausted { endpoint, reset })) => {
+                assert_eq!(endpoint, "statuses/show/:id");
+                assert!(reset.is_some());
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rate_limit_exceeded() {
+        let mut pool = TokenPool::new();
+        pool.add(tokens(1));
+        pool.add(tokens(2));
+
+        let result: Result<()> = Err(twitter_error(TwitterError::RATE_LIMIT_EXCEEDED));
+        pool.report(&tokens(1), "statuses/update", &result);
+        assert_eq!(pool.remaining(&tokens(1), "statuses/update"), Some(0));
+        assert_eq!(pool.pick("statuses/update"), Some(&tokens(2)));
+        assert_eq!(pool.pick("statuses/show/:id"), Some(&tokens(1)));
+    }
+
+    #[actix_rt::test]
+    async fn record_successful_rate_limit() {
+        let mut pool = TokenPool::new().limit("statuses/show/:id", 900);
+        pool.add(tokens(1));
+
+        let reset = Utc::now() + Duration::minutes(5);
+        let id = pool
+            .run_with_rate_limit("statuses/show/:id", |tokens| async move {
+                let rate_limit = RateLimit {
+                    limit: 900,
+                    remaining: 3,
+                    reset,
+                };
+                Ok((tokens.user_id(), Some(rate_limit)))
+            })
+            .await
+            .unwrap();
+        assert_eq!(id, Some(1));
+        assert_eq!(pool.remaining(&tokens(1), "statuses/show/:id"), Some(3));
+    }
+
+    #[actix_rt::test]
+    async fn drop_invalid_tokens() {
+        let dropped = Arc::new(Mutex::new(Vec::new()));
+        let hook = dropped.clone();
+        let mut pool = TokenPool::new().on_invalid(move |tokens| {
+            hook.lock().unwrap().push(tokens.user_id().unwrap());
+        });
+        pool.add(tokens(1));
+        pool.add(tokens(2));
+
+        let result: Result<()> = pool
+            .run("statuses/show/:id", |_| async {
+                Err(twitter_error(TwitterError::INVALID_OR_EXPIRED_TOKEN))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(*dropped.lock().unwrap(), vec![1]);
+        assert_eq!(pool.tokens().collect::<Vec<_>>(), vec![&tokens(2)]);
+
+        let user_id = pool
+            .run(
+                "statuses/show/:id",
+                |tokens| async move { Ok(tokens.user_id()) },
+            )
+            .await
+            .unwrap();
+        assert_eq!(user_id, Some(2));
+
+        pool.invalidate(&tokens(2));
+        assert!(pool.is_empty());
+        assert_eq!(*dropped.lock().unwrap(), vec![1, 2]);
+        match pool.acquire("statuses/show/:id") {
+            Err(Error::PoolError(PoolError::Empty)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
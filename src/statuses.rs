@@ -4,29 +4,30 @@ use crate::*;
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display};
 
-pub fn update(tokens: &TokenKeys, status: String) -> Update {
-    Update::new(tokens, status)
+pub fn update<'a>(auth: impl Into<Auth<'a>>, status: String) -> Update<'a> {
+    Update::new(auth, status)
 }
 
 pub struct Update<'a> {
-    tokens: &'a TokenKeys,
+    auth: Auth<'a>,
     status: String,
-    in_reply_to_status_id: Option<u64>,
+    in_reply_to_status_id: Option<TweetId>,
     auto_populate_reply_metadata: Option<bool>,
-    exclude_reply_user_ids: Vec<u64>,
+    exclude_reply_user_ids: Vec<UserId>,
     attachment_url: Option<String>,
-    media_ids: Vec<u64>, // 4 photo ids at most
+    media_ids: Vec<MediaId>,
     possibly_sensitive: Option<bool>,
     lat: Option<f64>,
     long: Option<f64>,
     place_id: Option<String>,
     display_coordinates: Option<bool>,
+    tweet_mode: Option<TweetMode>,
 }
 
 impl<'a> Update<'a> {
-    pub fn new(tokens: &'a TokenKeys, status: String) -> Self {
+    pub fn new(auth: impl Into<Auth<'a>>, status: String) -> Self {
         Self {
-            tokens,
+            auth: auth.into(),
             status,
             in_reply_to_status_id: None,
             auto_populate_reply_metadata: None,
@@ -38,10 +39,99 @@ impl<'a> Update<'a> {
             long: None,
             place_id: None,
             display_coordinates: None,
+            tweet_mode: None,
         }
     }
 
+    pub fn in_reply_to_status_id(mut self, in_reply_to_status_id: impl Into<TweetId>) -> Self {
+        self.in_reply_to_status_id = Some(in_reply_to_status_id.into());
+        self
+    }
+
+    pub fn auto_populate_reply_metadata(mut self, auto_populate_reply_metadata: bool) -> Self {
+        self.auto_populate_reply_metadata = Some(auto_populate_reply_metadata);
+        self
+    }
+
+    pub fn exclude_reply_user_ids(mut self, exclude_reply_user_ids: Vec<UserId>) -> Self {
+        self.exclude_reply_user_ids = exclude_reply_user_ids;
+        self
+    }
+
+    pub fn attachment_url(mut self, attachment_url: String) -> Self {
+        self.attachment_url = Some(attachment_url);
+        self
+    }
+
+    pub fn media_ids(mut self, media_ids: Vec<MediaId>) -> Self {
+        self.media_ids = media_ids;
+        self
+    }
+
+    pub fn possibly_sensitive(mut self, possibly_sensitive: bool) -> Self {
+        self.possibly_sensitive = Some(possibly_sensitive);
+        self
+    }
+
+    pub fn lat(mut self, lat: f64) -> Self {
+        self.lat = Some(lat);
+        self
+    }
+
+    pub fn long(mut self, long: f64) -> Self {
+        self.long = Some(long);
+        self
+    }
+
+    pub fn place_id(mut self, place_id: String) -> Self {
+        self.place_id = Some(place_id);
+        self
+    }
+
+    pub fn display_coordinates(mut self, display_coordinates: bool) -> Self {
+        self.display_coordinates = Some(display_coordinates);
+        self
+    }
+
+    pub fn tweet_mode(mut self, tweet_mode: TweetMode) -> Self {
+        self.tweet_mode = Some(tweet_mode);
+        self
+    }
+
+    /// The checks `send` runs before making the request.
+    pub fn validate(&self) -> std::result::Result<(), ValidationError> {
+        let parsed = text::parse_tweet(&self.status);
+        let has_attachment = !self.media_ids.is_empty() || self.attachment_url.is_some();
+        if self.status.is_empty() {
+            if !has_attachment {
+                return Err(ValidationError::EmptyStatus);
+            }
+        } else if parsed.weighted_length > text::MAX_WEIGHTED_LENGTH {
+            return Err(ValidationError::TooLong {
+                weighted_length: parsed.weighted_length,
+            });
+        } else if !parsed.valid {
+            return Err(ValidationError::InvalidCharacters);
+        }
+        if self.media_ids.len() > MAX_MEDIA_IDS {
+            return Err(ValidationError::TooManyMedia(self.media_ids.len()));
+        }
+        if let Some(lat) = self.lat {
+            if !(-90.0..=90.0).contains(&lat) {
+                return Err(ValidationError::Latitude(lat));
+            }
+        }
+        if let Some(long) = self.long {
+            if !(-180.0..=180.0).contains(&long) {
+                return Err(ValidationError::Longitude(long));
+            }
+        }
+        Ok(())
+    }
+
     pub async fn send(self) -> Result<Tweet> {
+        self.auth.check("statuses/update", &[AuthKind::User])?;
+        self.validate()?;
         let url = "https://api.twitter.com/1.1/statuses/update.json";
         let mut request = Request::post(url);
         request.query("status", self.status);
@@ -56,31 +146,81 @@ impl<'a> Update<'a> {
         _opt_param!(self, request, long);
         _opt_param!(self, request, place_id);
         _opt_param!(self, request, display_coordinates);
+        _opt_param!(self, request, tweet_mode);
 
-        Ok(request.send(self.tokens).await?.json().await?)
+        request.send_json(self.auth).await
+    }
+}
+
+/// Photos per Tweet; a video or GIF must be alone.
+const MAX_MEDIA_IDS: usize = 4;
+
+/// A `statuses/update` the API would reject.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    /// Only allowed with media or an `attachment_url`.
+    EmptyStatus,
+    /// The weighted length is over `text::MAX_WEIGHTED_LENGTH`.
+    TooLong {
+        weighted_length: usize,
+    },
+    /// U+FFFE, U+FEFF or U+FFFF.
+    InvalidCharacters,
+    TooManyMedia(usize),
+    Latitude(f64),
+    Longitude(f64),
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EmptyStatus => write!(f, "status is empty"),
+            Self::TooLong { weighted_length } => write!(
+                f,
+                "status is too long: {} of {}",
+                weighted_length,
+                text::MAX_WEIGHTED_LENGTH
+            ),
+            Self::InvalidCharacters => write!(f, "status contains invalid characters"),
+            Self::TooManyMedia(n) => {
+                write!(f, "{} media ids given, {} at most", n, MAX_MEDIA_IDS)
+            }
+            Self::Latitude(lat) => write!(f, "latitude {} is not within -90..=90", lat),
+            Self::Longitude(long) => write!(f, "longitude {} is not within -180..=180", long),
+        }
     }
 }
 
-pub fn destroy(tokens: &TokenKeys, id: u64) -> Destroy {
-    Destroy::new(tokens, id)
+impl std::error::Error for ValidationError {}
+
+pub fn destroy<'a>(auth: impl Into<Auth<'a>>, id: impl Into<TweetId>) -> Destroy<'a> {
+    Destroy::new(auth, id)
 }
 
 pub struct Destroy<'a> {
-    tokens: &'a TokenKeys,
-    id: u64,
+    auth: Auth<'a>,
+    id: TweetId,
     trim_user: Option<bool>,
+    tweet_mode: Option<TweetMode>,
 }
 
 impl<'a> Destroy<'a> {
-    pub fn new(tokens: &'a TokenKeys, id: u64) -> Self {
+    pub fn new(auth: impl Into<Auth<'a>>, id: impl Into<TweetId>) -> Self {
         Self {
-            tokens,
-            id,
+            auth: auth.into(),
+            id: id.into(),
             trim_user: None,
+            tweet_mode: None,
         }
     }
 
+    pub fn tweet_mode(mut self, tweet_mode: TweetMode) -> Self {
+        self.tweet_mode = Some(tweet_mode);
+        self
+    }
+
     pub async fn send(self) -> Result<Tweet> {
+        self.auth.check("statuses/destroy/:id", &[AuthKind::User])?;
         let url = format!(
             "https://api.twitter.com/1.1/statuses/destroy/{}.json",
             self.id
@@ -89,38 +229,53 @@ impl<'a> Destroy<'a> {
         if let Some(trim_user) = self.trim_user {
             request.parameter("trim_user", trim_user);
         }
-        Ok(request.send(self.tokens).await?.json().await?)
+        _opt_param!(self, request, tweet_mode);
+        request.send_json(self.auth).await
     }
 }
 
-pub fn show(tokens: &TokenKeys, id: u64) -> Show {
-    Show::new(tokens, id)
+pub fn show<'a>(auth: impl Into<Auth<'a>>, id: impl Into<TweetId>) -> Show<'a> {
+    Show::new(auth, id)
 }
 
 pub struct Show<'a> {
-    tokens: &'a TokenKeys,
-    id: u64,
+    auth: Auth<'a>,
+    id: TweetId,
     trim_user: Option<bool>,
     include_my_retweet: Option<bool>,
     include_entities: Option<bool>,
     include_ext_alt_text: Option<bool>,
     include_card_uri: Option<bool>,
+    tweet_mode: Option<TweetMode>,
 }
 
 impl<'a> Show<'a> {
-    pub fn new(tokens: &'a TokenKeys, id: u64) -> Self {
+    pub fn new(auth: impl Into<Auth<'a>>, id: impl Into<TweetId>) -> Self {
         Self {
-            tokens,
-            id,
+            auth: auth.into(),
+            id: id.into(),
             trim_user: None,
             include_my_retweet: None,
             include_entities: None,
             include_ext_alt_text: None,
             include_card_uri: None,
+            tweet_mode: None,
         }
     }
 
+    pub fn tweet_mode(mut self, tweet_mode: TweetMode) -> Self {
+        self.tweet_mode = Some(tweet_mode);
+        self
+    }
+
     pub async fn send(self) -> Result<Tweet> {
+        Ok(self.send_with_rate_limit().await?.0)
+    }
+
+    /// Also returns the `x-rate-limit-*` headers, e.g. for `TokenPool::run_with_rate_limit`.
+    pub async fn send_with_rate_limit(self) -> Result<(Tweet, Option<RateLimit>)> {
+        self.auth
+            .check("statuses/show/:id", &[AuthKind::User, AuthKind::AppOnly])?;
         let url = "https://api.twitter.com/1.1/statuses/show.json";
         let mut request = Request::get(url);
         request.query("id", self.id);
@@ -138,13 +293,32 @@ impl<'a> Show<'a> {
         opt_query!(include_entities);
         opt_query!(include_ext_alt_text);
         opt_query!(include_card_uri);
+        opt_query!(tweet_mode);
 
-        Ok(request.send(self.tokens).await?.json().await?)
+        request.send_json_with_rate_limit(self.auth).await
     }
 }
 
-pub fn oembed(tokens: &TokenKeys, url: String) -> OEmbedRequest {
-    OEmbedRequest::new(tokens, url)
+/// `Extended` returns the complete text in `Tweet.full_text` instead of a
+/// `text` truncated to 140 characters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TweetMode {
+    Compat,
+    Extended,
+}
+
+impl Display for TweetMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Compat => write!(f, "compat"),
+            Self::Extended => write!(f, "extended"),
+        }
+    }
+}
+
+/// Embed HTML for the Tweet at `url`, which may be a parsed `TweetLink`.
+pub fn oembed<'a>(auth: impl Into<Auth<'a>>, url: impl ToString) -> OEmbedRequest<'a> {
+    OEmbedRequest::new(auth, url)
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -182,7 +356,7 @@ impl Display for Theme {
 }
 
 pub struct OEmbedRequest<'a> {
-    tokens: &'a TokenKeys,
+    auth: Auth<'a>,
     url: String,
     maxwidth: Option<u16>,
     hide_media: Option<bool>,
@@ -198,10 +372,10 @@ pub struct OEmbedRequest<'a> {
 }
 
 impl<'a> OEmbedRequest<'a> {
-    pub fn new(tokens: &'a TokenKeys, url: String) -> Self {
+    pub fn new(auth: impl Into<Auth<'a>>, url: impl ToString) -> Self {
         Self {
-            tokens,
-            url,
+            auth: auth.into(),
+            url: url.to_string(),
             maxwidth: None,
             hide_media: None,
             hide_thread: None,
@@ -217,7 +391,10 @@ impl<'a> OEmbedRequest<'a> {
     }
 
     pub async fn send(self) -> Result<OEmbed> {
-        let url = "https://publish.twitter.com/oembed";
+        self.send_to("https://publish.twitter.com/oembed").await
+    }
+
+    pub(crate) async fn send_to(self, url: &str) -> Result<OEmbed> {
         let mut request = Request::get(url);
         request.query("url", self.url);
 
@@ -233,7 +410,7 @@ impl<'a> OEmbedRequest<'a> {
         _opt_query!(self, request, widget_type);
         _opt_query!(self, request, dnt);
 
-        Ok(request.send(self.tokens).await?.json().await?)
+        request.send_json(self.auth).await
     }
 }
 
@@ -251,3 +428,81 @@ pub struct OEmbed {
     pub provider_url: String,
     pub version: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens() -> TokenKeys {
+        TokenKeys::new(KeyPair::new("ck".to_string(), "cs".to_string()))
+            .oauth_tokens(KeyPair::new("at".to_string(), "as".to_string()))
+    }
+
+    #[test]
+    fn validate() {
+        let tokens = tokens();
+        assert_eq!(update(&tokens, "Hello".to_string()).validate(), Ok(()));
+        assert_eq!(
+            update(&tokens, "\u{4f60}".repeat(141)).validate(),
+            Err(ValidationError::TooLong {
+                weighted_length: 282
+            })
+        );
+        assert_eq!(
+            update(&tokens, String::new()).validate(),
+            Err(ValidationError::EmptyStatus)
+        );
+        assert_eq!(
+            update(&tokens, String::new())
+                .media_ids(vec![MediaId(1)])
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            update(&tokens, "\u{feff}".to_string()).validate(),
+            Err(ValidationError::InvalidCharacters)
+        );
+        let media_ids = (1..=5).map(MediaId).collect();
+        assert_eq!(
+            update(&tokens, "Photos".to_string())
+                .media_ids(media_ids)
+                .validate(),
+            Err(ValidationError::TooManyMedia(5))
+        );
+        assert_eq!(
+            update(&tokens, "Here".to_string())
+                .lat(37.78)
+                .long(-122.4)
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            update(&tokens, "Here".to_string()).lat(91.0).validate(),
+            Err(ValidationError::Latitude(91.0))
+        );
+        assert_eq!(
+            update(&tokens, "Here".to_string()).long(-180.5).validate(),
+            Err(ValidationError::Longitude(-180.5))
+        );
+    }
+
+    #[test]
+    fn tweet_link() {
+        let link: crate::permalink::TweetLink =
+            "https://x.com/TwitterDev/status/1050118621198921728"
+                .parse()
+                .unwrap();
+        assert_eq!(show(&tokens(), &link).id, 1050118621198921728);
+        assert_eq!(destroy(&tokens(), link).id, 1050118621198921728);
+    }
+
+    #[actix_rt::test]
+    async fn reject_before_sending() {
+        match update(&tokens(), "a".repeat(281)).send().await {
+            Err(Error::ValidationError(ValidationError::TooLong { weighted_length })) => {
+                assert_eq!(weighted_length, 281)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Working with tweet text.
+//!
+//! Entity `indices` count Unicode code points, not bytes, so they can't index
+//! a `str` directly once the text contains anything outside ASCII.
+
+mod extract;
+mod tlds;
+mod weighted;
+
+use crate::utils::percent_encode;
+use crate::*;
+pub use extract::{
+    extract_cashtags, extract_entities, extract_hashtags, extract_mentions,
+    extract_reply_screen_name, extract_urls, ExtractedEntities, Mention,
+};
+use std::ops::Range;
+pub use weighted::{
+    parse_tweet, parse_tweet_with_config, weighted_length, Config, ParsedTweet,
+    MAX_WEIGHTED_LENGTH, TRANSFORMED_URL_LENGTH,
+};
+
+/// The byte range of the code points `indices[0]..indices[1]` of `text`.
+pub fn byte_range(text: &str, indices: [usize; 2]) -> Option<Range<usize>> {
+    let [start, end] = indices;
+    if start > end {
+        return None;
+    }
+    let mut offsets = text
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(text.len()));
+    let start_byte = offsets.nth(start)?;
+    let end_byte = if end == start {
+        start_byte
+    } else {
+        offsets.nth(end - start - 1)?
+    };
+    Some(start_byte..end_byte)
+}
+
+/// The code points `indices[0]..indices[1]` of `text`.
+pub fn slice(text: &str, indices: [usize; 2]) -> Option<&str> {
+    byte_range(text, indices).map(|range| &text[range])
+}
+
+/// An entity located in the text by code point `indices`.
+pub trait Indices {
+    fn indices(&self) -> [usize; 2];
+
+    /// The part of `text` this entity covers.
+    fn slice<'t>(&self, text: &'t str) -> Option<&'t str> {
+        slice(text, self.indices())
+    }
+
+    fn byte_range(&self, text: &str) -> Option<Range<usize>> {
+        byte_range(text, self.indices())
+    }
+}
+
+/// Undoes the escaping of `&`, `<` and `>` in Tweet text.
+pub fn unescape_html(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = ["&amp;", "&lt;", "&gt;", "&quot;", "&#39;"]
+            .iter()
+            .zip(&['&', '<', '>', '"', '\''])
+            .find(|(entity, _)| rest.starts_with(*entity));
+        match entity {
+            Some((entity, c)) => {
+                unescaped.push(*c);
+                rest = &rest[entity.len()..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+fn escape_html(text: &str, html: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+}
+
+enum Link<'e> {
+    Hashtag(&'e Hashtag),
+    Symbol(&'e Symbol),
+    Mention(&'e UserMention),
+    Url {
+        url: &'e str,
+        display_url: Option<&'e str>,
+        expanded_url: Option<&'e str>,
+    },
+}
+
+/// The displayed part of the complete text, split into plain runs and links.
+fn segments(tweet: &Tweet) -> Vec<(String, Option<Link<'_>>)> {
+    let text: Vec<char> = unescape_html(tweet.complete_text()).chars().collect();
+    let entities = tweet.complete_entities();
+    let [start, end] = tweet
+        .complete_display_text_range()
+        .unwrap_or([0, text.len()]);
+    let end = end.min(text.len());
+
+    let mut links: Vec<([usize; 2], Link)> = Vec::new();
+    links.extend(
+        entities
+            .hashtags
+            .iter()
+            .map(|e| (e.indices, Link::Hashtag(e))),
+    );
+    links.extend(
+        entities
+            .symbols
+            .iter()
+            .map(|e| (e.indices, Link::Symbol(e))),
+    );
+    links.extend(
+        entities
+            .user_mentions
+            .iter()
+            .map(|e| (e.indices, Link::Mention(e))),
+    );
+    links.extend(entities.urls.iter().map(|e| {
+        let link = Link::Url {
+            url: &e.url,
+            display_url: e.display_url.as_deref(),
+            expanded_url: e.expanded_url.as_deref(),
+        };
+        (e.indices, link)
+    }));
+    links.extend(entities.media.iter().map(|e| {
+        let link = Link::Url {
+            url: &e.url,
+            display_url: Some(&e.display_url),
+            expanded_url: Some(&e.expanded_url),
+        };
+        (e.indices, link)
+    }));
+    links.sort_by_key(|([start, _], _)| *start);
+
+    let mut segments = Vec::new();
+    let mut position = start;
+    for ([link_start, link_end], link) in links {
+        if link_start < position || link_end > end || link_start > link_end {
+            continue;
+        }
+        if link_start > position {
+            segments.push((text[position..link_start].iter().collect(), None));
+        }
+        segments.push((text[link_start..link_end].iter().collect(), Some(link)));
+        position = link_end;
+    }
+    if position < end {
+        segments.push((text[position..end].iter().collect(), None));
+    }
+    segments
+}
+
+/// The displayed text with t.co links replaced by their `expanded_url`.
+pub fn render_text(tweet: &Tweet) -> String {
+    segments(tweet)
+        .into_iter()
+        .map(|(text, link)| match link {
+            Some(Link::Url {
+                expanded_url: Some(expanded_url),
+                ..
+            }) => expanded_url.to_string(),
+            _ => text,
+        })
+        .collect()
+}
+
+/// The displayed text as HTML, linking hashtags, mentions, cashtags and URLs
+/// to twitter.com and their `expanded_url`.
+pub fn render_html(tweet: &Tweet) -> String {
+    let mut html = String::new();
+    for (text, link) in segments(tweet) {
+        let (href, text) = match link {
+            None => {
+                escape_html(&text, &mut html);
+                continue;
+            }
+            Some(Link::Hashtag(hashtag)) => (
+                format!(
+                    "https://twitter.com/hashtag/{}",
+                    percent_encode(&hashtag.text)
+                ),
+                text,
+            ),
+            Some(Link::Symbol(symbol)) => (
+                format!(
+                    "https://twitter.com/search?q=%24{}",
+                    percent_encode(&symbol.text)
+                ),
+                text,
+            ),
+            Some(Link::Mention(mention)) => {
+                (format!("https://twitter.com/{}", mention.screen_name), text)
+            }
+            Some(Link::Url {
+                url,
+                display_url,
+                expanded_url,
+            }) => (
+                expanded_url.unwrap_or(url).to_string(),
+                display_url.unwrap_or(url).to_string(),
+            ),
+        };
+        html.push_str("<a href=\"");
+        escape_html(&href, &mut html);
+        html.push_str("\">");
+        escape_html(&text, &mut html);
+        html.push_str("</a>");
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_points() {
+        let text = "caf\u{e9} \u{1f600} #rust";
+        assert_eq!(slice(text, [0, 4]), Some("caf\u{e9}"));
+        assert_eq!(slice(text, [5, 6]), Some("\u{1f600}"));
+        assert_eq!(slice(text, [7, 12]), Some("#rust"));
+        assert_eq!(byte_range(text, [7, 12]), Some(11..16));
+        assert_eq!(slice(text, [12, 12]), Some(""));
+        assert_eq!(slice(text, [7, 13]), None);
+        assert_eq!(slice(text, [6, 5]), None);
+    }
+
+    #[test]
+    fn unescape() {
+        assert_eq!(
+            unescape_html("Q&amp;A &lt;3 &gt;_&lt; &amp;amp; &copy; &"),
+            "Q&A <3 >_< &amp; &copy; &"
+        );
+    }
+}
@@ -0,0 +1,579 @@
+//! Finding entities in text before it is posted, following twitter-text.
+
+use super::tlds::TLDS;
+use super::Indices;
+use crate::{Hashtag, Symbol, Url};
+use std::collections::HashMap;
+use unicode_normalization::char::is_combining_mark;
+
+/// A mention of a user or list, e.g. `@TwitterDev` or `@TwitterDev/team`.
+///
+/// Unlike `UserMention`, it has no `id` or `name`, which only the API knows,
+/// and a mention of a list carries its `list_slug`, without the `/`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mention {
+    pub screen_name: String,
+    pub list_slug: Option<String>,
+    pub indices: [usize; 2],
+}
+
+impl Indices for Mention {
+    fn indices(&self) -> [usize; 2] {
+        self.indices
+    }
+}
+
+/// Entities of a draft, named like `Entity`, except that `user_mentions` are
+/// `Mention`s and there is no media or poll, which are not part of the text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtractedEntities {
+    pub hashtags: Vec<Hashtag>,
+    pub urls: Vec<Url>,
+    pub user_mentions: Vec<Mention>,
+    pub symbols: Vec<Symbol>,
+}
+
+/// All entities of `text`. Hashtags, mentions and cashtags within URLs are
+/// part of the URL.
+pub fn extract_entities(text: &str) -> ExtractedEntities {
+    let chars: Vec<char> = text.chars().collect();
+    let urls = url_ranges(&chars);
+    let outside_urls = |[start, end]: [usize; 2]| {
+        urls.iter()
+            .all(|[url_start, url_end]| end <= *url_start || start >= *url_end)
+    };
+    ExtractedEntities {
+        hashtags: hashtags(&chars)
+            .filter(|hashtag| outside_urls(hashtag.indices))
+            .collect(),
+        urls: urls.iter().map(|indices| url(&chars, *indices)).collect(),
+        user_mentions: mentions(&chars)
+            .filter(|mention| outside_urls(mention.indices))
+            .collect(),
+        symbols: cashtags(&chars)
+            .filter(|symbol| outside_urls(symbol.indices))
+            .collect(),
+    }
+}
+
+/// URLs, with or without a protocol, e.g. `https://example.com/a` and `example.com`.
+pub fn extract_urls(text: &str) -> Vec<Url> {
+    extract_entities(text).urls
+}
+
+/// Hashtags in `text`, their `text` without the `#`.
+pub fn extract_hashtags(text: &str) -> Vec<Hashtag> {
+    extract_entities(text).hashtags
+}
+
+/// Cashtags like `$TWTR` in `text`, their `text` without the `$`.
+pub fn extract_cashtags(text: &str) -> Vec<Symbol> {
+    extract_entities(text).symbols
+}
+
+/// Mentions of users and lists.
+pub fn extract_mentions(text: &str) -> Vec<Mention> {
+    extract_entities(text).user_mentions
+}
+
+/// The user a Tweet starting with a mention replies to.
+pub fn extract_reply_screen_name(text: &str) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let start = chars
+        .iter()
+        .position(|c| !(c.is_whitespace() || *c == '\u{200e}' || *c == '\u{200f}'))?;
+    match_mention(&chars, start).map(|mention| mention.screen_name)
+}
+
+fn url(chars: &[char], [start, end]: [usize; 2]) -> Url {
+    Url {
+        indices: [start, end],
+        url: chars[start..end].iter().collect(),
+        display_url: None,
+        expanded_url: None,
+        extra: HashMap::new(),
+    }
+}
+
+fn is_hashtag_special(c: char) -> bool {
+    matches!(
+        c,
+        '_' | '\u{200c}'
+            | '\u{200d}'
+            | '\u{a67e}'
+            | '\u{5be}'
+            | '\u{5f3}'
+            | '\u{5f4}'
+            | '\u{ff5e}'
+            | '\u{301c}'
+            | '\u{309b}'
+            | '\u{309c}'
+            | '\u{30a0}'
+            | '\u{30fb}'
+            | '\u{3003}'
+            | '\u{f0b}'
+            | '\u{f0c}'
+            | '\u{b7}'
+    )
+}
+
+fn is_hashtag_alpha(c: char) -> bool {
+    c.is_alphabetic() || is_combining_mark(c)
+}
+
+fn is_hashtag_alphanumeric(c: char) -> bool {
+    is_hashtag_alpha(c) || c.is_numeric() || is_hashtag_special(c)
+}
+
+fn hashtags(chars: &[char]) -> impl Iterator<Item = Hashtag> + '_ {
+    (0..chars.len()).filter_map(move |start| {
+        if !matches!(chars[start], '#' | '＃') {
+            return None;
+        }
+        let preceding = start.checked_sub(1).map(|i| chars[i]);
+        if let Some(c) = preceding {
+            if c == '&' || (is_hashtag_alphanumeric(c) && c != '\u{fe0e}' && c != '\u{fe0f}') {
+                return None;
+            }
+        }
+        let text_start = start + 1;
+        let mut end = text_start;
+        while chars.get(end).copied().is_some_and(is_hashtag_alphanumeric) {
+            end += 1;
+        }
+        let text = &chars[text_start..end];
+        // Keycaps like #️⃣ and all-digit tags are not hashtags.
+        if !text.iter().any(|c| is_hashtag_alpha(*c))
+            || matches!(chars.get(text_start), Some('\u{fe0f}') | Some('\u{20e3}'))
+        {
+            return None;
+        }
+        let following = &chars[end..];
+        if matches!(following.first(), Some('#') | Some('＃'))
+            || following.starts_with(&[':', '/', '/'])
+        {
+            return None;
+        }
+        Some(Hashtag {
+            indices: [start, end],
+            text: text.iter().collect(),
+            extra: HashMap::new(),
+        })
+    })
+}
+
+fn cashtags(chars: &[char]) -> impl Iterator<Item = Symbol> + '_ {
+    (0..chars.len()).filter_map(move |start| {
+        if chars[start] != '$'
+            || start
+                .checked_sub(1)
+                .is_some_and(|i| !chars[i].is_whitespace())
+        {
+            return None;
+        }
+        let letters = |from: usize, max: usize| {
+            chars[from..]
+                .iter()
+                .take(max + 1)
+                .take_while(|c| c.is_ascii_alphabetic())
+                .count()
+        };
+        let mut end = start + 1;
+        let len = letters(end, 6);
+        if len == 0 || len > 6 {
+            return None;
+        }
+        end += len;
+        if matches!(chars.get(end), Some('.') | Some('_')) {
+            let len = letters(end + 1, 2);
+            if (1..=2).contains(&len) {
+                end += 1 + len;
+            }
+        }
+        match chars.get(end) {
+            Some(c) if !(c.is_whitespace() || c.is_ascii_punctuation()) => None,
+            _ => Some(Symbol {
+                indices: [start, end],
+                text: chars[start + 1..end].iter().collect(),
+                extra: HashMap::new(),
+            }),
+        }
+    })
+}
+
+fn is_screen_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Whether a mention may start at `start`, including after `RT` or `RT:`.
+fn is_valid_mention_start(chars: &[char], start: usize) -> bool {
+    let preceding = match start.checked_sub(1) {
+        None => return true,
+        Some(i) => chars[i],
+    };
+    if !(is_screen_name_char(preceding) || "!#$%&*@＠".contains(preceding)) {
+        return true;
+    }
+    let before = &chars[..start];
+    let before = before.strip_suffix(&[':']).unwrap_or(before);
+    match before.len().checked_sub(2) {
+        Some(rt)
+            if before[rt..]
+                .iter()
+                .collect::<String>()
+                .eq_ignore_ascii_case("rt") =>
+        {
+            rt == 0 || !(is_screen_name_char(before[rt - 1]) || "+~.-".contains(before[rt - 1]))
+        }
+        _ => false,
+    }
+}
+
+fn match_mention(chars: &[char], start: usize) -> Option<Mention> {
+    if !matches!(chars.get(start), Some('@') | Some('＠')) {
+        return None;
+    }
+    let name_start = start + 1;
+    let name_len = chars[name_start..]
+        .iter()
+        .take(20)
+        .take_while(|c| is_screen_name_char(**c))
+        .count();
+    if name_len == 0 {
+        return None;
+    }
+    let mut end = name_start + name_len;
+    let mut list_slug = None;
+    if chars.get(end) == Some(&'/') && chars.get(end + 1).is_some_and(|c| c.is_ascii_alphabetic()) {
+        let slug_len = chars[end + 1..]
+            .iter()
+            .take(25)
+            .take_while(|c| is_screen_name_char(**c) || **c == '-')
+            .count();
+        list_slug = Some(chars[end + 1..end + 1 + slug_len].iter().collect());
+        end += 1 + slug_len;
+    }
+    let following = &chars[end..];
+    if matches!(following.first(), Some('@') | Some('＠'))
+        || following.first().copied().is_some_and(is_latin_accent)
+        || following.starts_with(&[':', '/', '/'])
+    {
+        return None;
+    }
+    Some(Mention {
+        screen_name: chars[name_start..name_start + name_len].iter().collect(),
+        list_slug,
+        indices: [start, end],
+    })
+}
+
+fn mentions(chars: &[char]) -> impl Iterator<Item = Mention> + '_ {
+    (0..chars.len())
+        .filter(move |start| is_valid_mention_start(chars, *start))
+        .filter_map(move |start| match_mention(chars, start))
+}
+
+pub(super) fn url_ranges(chars: &[char]) -> Vec<[usize; 2]> {
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match match_url(chars, i) {
+            Some(end) => {
+                ranges.push([i, end]);
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    ranges
+}
+
+fn is_latin_accent(c: char) -> bool {
+    matches!(c,
+        '\u{c0}'..='\u{d6}'
+        | '\u{d8}'..='\u{f6}'
+        | '\u{f8}'..='\u{24f}'
+        | '\u{253}'..='\u{254}'
+        | '\u{256}'..='\u{257}'
+        | '\u{259}'
+        | '\u{25b}'
+        | '\u{263}'
+        | '\u{268}'
+        | '\u{26f}'
+        | '\u{272}'
+        | '\u{289}'
+        | '\u{28b}'
+        | '\u{2bb}'
+        | '\u{300}'..='\u{36f}'
+        | '\u{1e00}'..='\u{1eff}')
+}
+
+fn is_cyrillic(c: char) -> bool {
+    ('\u{400}'..='\u{4ff}').contains(&c)
+}
+
+/// Bidirectional embeddings, isolates and marks, common around URLs in
+/// right-to-left text.
+fn is_directional(c: char) -> bool {
+    matches!(c,
+        '\u{202a}'..='\u{202e}'
+        | '\u{2066}'..='\u{2069}'
+        | '\u{200e}'
+        | '\u{200f}'
+        | '\u{61c}')
+}
+
+/// Whether a URL may start after `c`.
+fn is_valid_preceding(c: Option<char>) -> bool {
+    match c {
+        None => true,
+        Some(c) if is_directional(c) => true,
+        Some(c) => !(c.is_ascii_alphanumeric() || "@＠$#＃\u{fffe}\u{feff}\u{ffff}".contains(c)),
+    }
+}
+
+/// Domains after a protocol may be internationalized; others are limited to
+/// ASCII and Latin accents, except for their top-level domain.
+fn is_domain_char(c: char, with_protocol: bool) -> bool {
+    if with_protocol {
+        !(c.is_ascii_punctuation()
+            || c.is_whitespace()
+            || c.is_control()
+            || "\u{fffe}\u{feff}\u{ffff}".contains(c))
+    } else {
+        c.is_ascii_alphanumeric() || is_latin_accent(c)
+    }
+}
+
+fn is_path_char(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || is_cyrillic(c)
+        || is_latin_accent(c)
+        || "!*';:=+,.$/%#[]-\u{2013}_~&|@".contains(c)
+}
+
+fn is_path_end(c: char) -> bool {
+    c.is_ascii_alphanumeric() || is_cyrillic(c) || is_latin_accent(c) || "=_#/+-".contains(c)
+}
+
+fn is_query_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!?*'@();:&=+$/%#[]-_.,~|".contains(c)
+}
+
+fn is_query_end(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-_&=#/".contains(c)
+}
+
+/// DNS allows 63 octets per label, internationalized ones counted in their
+/// punycode form.
+fn is_valid_label_length(label: &[char]) -> bool {
+    const MAX_LABEL_LENGTH: usize = 63;
+    if label.iter().all(char::is_ascii) {
+        return label.len() <= MAX_LABEL_LENGTH;
+    }
+    let label: String = label.iter().collect();
+    idna::punycode::encode_str(&label)
+        .is_some_and(|encoded| "xn--".len() + encoded.len() <= MAX_LABEL_LENGTH)
+}
+
+/// The length of the longest top-level domain `label` starts with, which must
+/// not run on into ASCII letters or digits, as in `example.comだよね` or
+/// `example.com-foo`.
+fn tld_len(label: &[char], next: Option<char>) -> Option<usize> {
+    (1..=label.len()).rev().find(|&len| {
+        let next = label.get(len).copied().or(next);
+        let tld: String = label[..len].iter().collect::<String>().to_lowercase();
+        !next.is_some_and(|c| c.is_ascii_alphanumeric() || c == '@')
+            && TLDS.binary_search(&tld.as_str()).is_ok()
+    })
+}
+
+/// Subdomains may contain underscores but, like the domain itself, neither
+/// start nor end with one or a hyphen.
+fn is_valid_domain(labels: &[&[char]], with_protocol: bool) -> bool {
+    let domain = match labels.last() {
+        Some(domain) => domain,
+        None => return false,
+    };
+    !domain.contains(&'_')
+        && labels.iter().all(|label| {
+            label
+                .first()
+                .is_some_and(|c| is_domain_char(*c, with_protocol))
+                && label
+                    .last()
+                    .is_some_and(|c| is_domain_char(*c, with_protocol))
+                && label
+                    .iter()
+                    .all(|c| is_domain_char(*c, with_protocol) || *c == '-' || *c == '_')
+                && is_valid_label_length(label)
+        })
+}
+
+fn starts_with_ignore_case(chars: &[char], prefix: &str) -> bool {
+    chars.len() >= prefix.len()
+        && chars
+            .iter()
+            .zip(prefix.chars())
+            .all(|(c, p)| c.to_ascii_lowercase() == p)
+}
+
+/// The longest slug of a t.co link.
+const MAX_TCO_SLUG_LENGTH: usize = 40;
+
+/// The end of a URL starting at `start`.
+fn match_url(chars: &[char], start: usize) -> Option<usize> {
+    let preceding = start.checked_sub(1).map(|i| chars[i]);
+    if !is_valid_preceding(preceding) {
+        return None;
+    }
+    let rest = &chars[start..];
+    let protocol = ["https://", "http://"]
+        .iter()
+        .find(|protocol| starts_with_ignore_case(rest, protocol))
+        .map(|protocol| protocol.len());
+    if protocol.is_none() && matches!(preceding, Some('-') | Some('_') | Some('.') | Some('/')) {
+        return None;
+    }
+    let with_protocol = protocol.is_some();
+    let domain_start = start + protocol.unwrap_or(0);
+
+    // The longest run of labels ending in a known top-level domain.
+    let mut run_end = domain_start;
+    while run_end < chars.len()
+        && (is_domain_char(chars[run_end], true) || "-_.".contains(chars[run_end]))
+    {
+        run_end += 1;
+    }
+    let labels: Vec<&[char]> = chars[domain_start..run_end].split(|c| *c == '.').collect();
+    let (tld_index, tld_len) = (1..labels.len()).rev().find_map(|i| {
+        if !is_valid_domain(&labels[..i], with_protocol) {
+            return None;
+        }
+        let tld = tld_len(labels[i], chars.get(run_end).copied())?;
+        is_valid_label_length(&labels[i][..tld]).then_some((i, tld))
+    })?;
+    let mut end = domain_start
+        + labels[..tld_index]
+            .iter()
+            .map(|label| label.len() + 1)
+            .sum::<usize>()
+        + tld_len;
+    let host: String = chars[domain_start..end].iter().collect();
+    if idna::domain_to_ascii(&host).is_err() {
+        return None;
+    }
+
+    // Port
+    if chars.get(end) == Some(&':') && chars.get(end + 1).is_some_and(|c| c.is_ascii_digit()) {
+        end += 1;
+        while chars.get(end).is_some_and(|c| c.is_ascii_digit()) {
+            end += 1;
+        }
+    }
+
+    // t.co slugs are alphanumeric, so anything after one is not part of it.
+    let slug_len = chars[(end + 1).min(chars.len())..]
+        .iter()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .count();
+    if with_protocol
+        && host.eq_ignore_ascii_case("t.co")
+        && chars.get(end) == Some(&'/')
+        && slug_len > 0
+    {
+        if slug_len > MAX_TCO_SLUG_LENGTH {
+            return None;
+        }
+        end += 1 + slug_len;
+    } else if chars.get(end) == Some(&'/') {
+        // Path, which may not end in punctuation unless it closes a parenthesis.
+        end += 1;
+        let mut depth = 0;
+        let mut valid_end = end;
+        while let Some(&c) = chars.get(end) {
+            match c {
+                '(' => depth += 1,
+                ')' if depth > 0 => depth -= 1,
+                c if is_path_char(c) => {}
+                _ => break,
+            }
+            end += 1;
+            if is_path_end(c) || (c == ')' && depth == 0) {
+                valid_end = end;
+            }
+        }
+        end = valid_end;
+    }
+
+    if chars.get(end) == Some(&'?') {
+        let mut query_end = end + 1;
+        let mut valid_end = end;
+        while let Some(&c) = chars.get(query_end) {
+            if !is_query_char(c) {
+                break;
+            }
+            query_end += 1;
+            if is_query_end(c) {
+                valid_end = query_end;
+            }
+        }
+        end = valid_end;
+    }
+
+    Some(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(text: &str) -> Vec<String> {
+        extract_urls(text).into_iter().map(|url| url.url).collect()
+    }
+
+    #[test]
+    fn urls_with_and_without_protocol() {
+        assert_eq!(urls("Hi http://test.co"), vec!["http://test.co"]);
+        assert_eq!(
+            urls("see example.com/a_b?x=1&y=2. And HTTPS://Twitter.com/#!/a, ok"),
+            vec!["example.com/a_b?x=1&y=2", "HTTPS://Twitter.com/#!/a"]
+        );
+        assert_eq!(
+            urls("(https://en.wikipedia.org/wiki/Rust_(programming_language))"),
+            vec!["https://en.wikipedia.org/wiki/Rust_(programming_language)"]
+        );
+        assert_eq!(
+            urls("t.co example.jp/a http://t.co/abc's"),
+            vec!["t.co", "example.jp/a", "http://t.co/abc"]
+        );
+        assert_eq!(
+            urls("http://example.com:8080/path/"),
+            vec!["http://example.com:8080/path/"]
+        );
+    }
+
+    #[test]
+    fn not_urls() {
+        assert!(urls("user@example.com").is_empty());
+        assert!(urls("@example.com #example.com $example.com").is_empty());
+        assert!(urls("example.notatld foo.invalidtld").is_empty());
+        assert!(urls("_example.com").is_empty());
+        assert!(urls("http://_example.com http://xn--はじめよう.com").is_empty());
+    }
+
+    #[test]
+    fn entities_within_urls() {
+        let entities = extract_entities("http://example.com/#anchor @user example.com/@home");
+        assert!(entities.hashtags.is_empty());
+        assert_eq!(entities.urls.len(), 2);
+        assert_eq!(entities.user_mentions.len(), 1);
+        assert_eq!(entities.user_mentions[0].indices, [27, 32]);
+    }
+
+    #[test]
+    fn indices() {
+        let extracted = extract_urls("caf\u{e9} http://example.com");
+        assert_eq!(extracted[0].indices, [5, 23]);
+    }
+}
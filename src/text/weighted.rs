@@ -0,0 +1,176 @@
+//! twitter-text weighted length.
+//!
+//! https://developer.twitter.com/en/docs/counting-characters
+
+use super::extract::url_ranges;
+use unicode_normalization::UnicodeNormalization;
+
+pub const MAX_WEIGHTED_LENGTH: usize = 280;
+/// Every URL counts as a t.co link of this length.
+pub const TRANSFORMED_URL_LENGTH: usize = 23;
+
+const SCALE: usize = 100;
+const DEFAULT_WEIGHT: usize = 200;
+
+/// Latin-1 through Georgian, general punctuation and spaces count once,
+/// everything else (CJK, emoji, ...) twice.
+fn weight(c: char) -> usize {
+    match c as u32 {
+        0..=4351 | 8192..=8205 | 8208..=8223 | 8242..=8247 => 100,
+        _ => DEFAULT_WEIGHT,
+    }
+}
+
+fn is_invalid(c: char) -> bool {
+    matches!(c, '\u{fffe}' | '\u{feff}' | '\u{ffff}')
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c, '\u{fe0e}' | '\u{fe0f}' | '\u{1f3fb}'..='\u{1f3ff}' | '\u{e0020}'..='\u{e007f}')
+}
+
+fn is_pictographic(c: char) -> bool {
+    matches!(c,
+        '\u{a9}'
+        | '\u{ae}'
+        | '\u{203c}'
+        | '\u{2049}'
+        | '\u{2122}'
+        | '\u{2139}'
+        | '\u{2194}'..='\u{2199}'
+        | '\u{21a9}'..='\u{21aa}'
+        | '\u{231a}'..='\u{231b}'
+        | '\u{2328}'
+        | '\u{23cf}'
+        | '\u{23e9}'..='\u{23f3}'
+        | '\u{23f8}'..='\u{23fa}'
+        | '\u{24c2}'
+        | '\u{25aa}'..='\u{25ab}'
+        | '\u{25b6}'
+        | '\u{25c0}'
+        | '\u{25fb}'..='\u{25fe}'
+        | '\u{2600}'..='\u{27bf}'
+        | '\u{2934}'..='\u{2935}'
+        | '\u{2b05}'..='\u{2b07}'
+        | '\u{2b1b}'..='\u{2b1c}'
+        | '\u{2b50}'
+        | '\u{2b55}'
+        | '\u{3030}'
+        | '\u{303d}'
+        | '\u{3297}'
+        | '\u{3299}'
+        | '\u{1f000}'..='\u{1f1e5}'
+        | '\u{1f200}'..='\u{1f3fa}'
+        | '\u{1f400}'..='\u{1faff}')
+}
+
+/// The number of code points of the emoji sequence at `i`, if any: flags,
+/// keycaps, and pictographs with their presentation selectors, skin tones,
+/// tags and zero-width-joined pictographs.
+fn emoji_len(chars: &[char], i: usize) -> Option<usize> {
+    let c = chars[i];
+    let next = chars.get(i + 1).copied();
+    if is_regional_indicator(c) {
+        return Some(if next.is_some_and(is_regional_indicator) {
+            2
+        } else {
+            1
+        });
+    }
+    if c.is_ascii_digit() || c == '#' || c == '*' {
+        return match (next, chars.get(i + 2)) {
+            (Some('\u{20e3}'), _) => Some(2),
+            (Some('\u{fe0f}'), Some('\u{20e3}')) => Some(3),
+            _ => None,
+        };
+    }
+    // Symbols like © are only emoji in emoji presentation.
+    if !is_pictographic(c) || (weight(c) < DEFAULT_WEIGHT && next != Some('\u{fe0f}')) {
+        return None;
+    }
+    let mut end = i + 1;
+    loop {
+        while chars.get(end).is_some_and(|c| is_emoji_modifier(*c)) {
+            end += 1;
+        }
+        match (chars.get(end), chars.get(end + 1)) {
+            (Some('\u{200d}'), Some(c)) if is_pictographic(*c) => end += 2,
+            _ => break,
+        }
+    }
+    Some(end - i)
+}
+
+/// The twitter-text configuration to count with. Both allow 280 and weigh
+/// code points alike, but only `V3` counts an emoji sequence as 2 as a whole.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Config {
+    V2,
+    #[default]
+    V3,
+}
+
+/// The outcome of counting a Tweet before posting it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsedTweet {
+    pub weighted_length: usize,
+    /// `weighted_length` in thousandths of `MAX_WEIGHTED_LENGTH`.
+    pub permillage: usize,
+    pub valid: bool,
+    /// Code points of the NFC-normalized text that fit within the limit,
+    /// up to the first invalid character.
+    pub valid_range: [usize; 2],
+}
+
+/// Counts `text` like the API does, after NFC normalization.
+pub fn parse_tweet(text: &str) -> ParsedTweet {
+    parse_tweet_with_config(text, Config::default())
+}
+
+pub fn parse_tweet_with_config(text: &str, config: Config) -> ParsedTweet {
+    let chars: Vec<char> = text.nfc().collect();
+    let urls = url_ranges(&chars);
+    let mut urls = urls.iter().peekable();
+
+    let mut weighted = 0;
+    let mut valid_end = 0;
+    let mut has_invalid = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let (len, char_weight) = match urls.peek() {
+            Some([start, end]) if *start == i => {
+                urls.next();
+                (end - start, TRANSFORMED_URL_LENGTH * SCALE)
+            }
+            _ => match emoji_len(&chars, i).filter(|_| config == Config::V3) {
+                Some(len) => (len, DEFAULT_WEIGHT),
+                None => {
+                    has_invalid |= is_invalid(chars[i]);
+                    (1, weight(chars[i]))
+                }
+            },
+        };
+        weighted += char_weight;
+        i += len;
+        if !has_invalid && weighted <= MAX_WEIGHTED_LENGTH * SCALE {
+            valid_end = i;
+        }
+    }
+
+    let weighted_length = weighted / SCALE;
+    ParsedTweet {
+        weighted_length,
+        permillage: weighted_length * 1000 / MAX_WEIGHTED_LENGTH,
+        valid: !chars.is_empty() && !has_invalid && weighted_length <= MAX_WEIGHTED_LENGTH,
+        valid_range: [0, valid_end],
+    }
+}
+
+/// The length counted against the 280 limit.
+pub fn weighted_length(text: &str) -> usize {
+    parse_tweet(text).weighted_length
+}
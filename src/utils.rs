@@ -1,13 +1,18 @@
+use crate::error::*;
 use crate::*;
 use actix_web::client::{Client, Connector};
+use actix_web::dev::{Decompress, Payload, PayloadStream};
 use actix_web::http::header::AUTHORIZATION;
 use chrono::{offset::Local, DateTime};
 use openssl::ssl::{SslConnector, SslMethod};
-use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
+use serde::de::DeserializeOwned;
 use std::fmt;
 
+pub type Response = awc::ClientResponse<Decompress<Payload<PayloadStream>>>;
+
 pub fn percent_encode(input: &str) -> String {
     const FRAGMENTS: &AsciiSet = &NON_ALPHANUMERIC
         .remove(b'-')
@@ -17,14 +22,44 @@ pub fn percent_encode(input: &str) -> String {
     utf8_percent_encode(input, FRAGMENTS).to_string()
 }
 
+pub fn percent_decode(input: &str) -> Option<String> {
+    percent_decode_str(input)
+        .decode_utf8()
+        .ok()
+        .map(|decoded| decoded.into_owned())
+}
+
+/// Parses `OAuth key="value", ...` into decoded key-value pairs.
+pub fn parse_oauth_header(header: &str) -> Option<Vec<(String, String)>> {
+    let (scheme, params) = header.trim().split_once(char::is_whitespace)?;
+    if !scheme.eq_ignore_ascii_case("OAuth") {
+        return None;
+    }
+    params
+        .split(',')
+        .map(|param| {
+            let (key, value) = param.trim().split_once('=')?;
+            let value = value.strip_prefix('"')?.strip_suffix('"')?;
+            Some((percent_decode(key)?, percent_decode(value)?))
+        })
+        .collect()
+}
+
+pub fn random_string(len: usize) -> String {
+    thread_rng().sample_iter(&Alphanumeric).take(len).collect()
+}
+
 fn generate_nonce() -> String {
-    thread_rng().sample_iter(&Alphanumeric).take(42).collect()
+    random_string(42)
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum HttpMethod {
     POST,
     GET,
+    PUT,
+    DELETE,
 }
 
 impl fmt::Display for HttpMethod {
@@ -32,6 +67,8 @@ impl fmt::Display for HttpMethod {
         match self {
             Self::POST => write!(f, "POST"),
             Self::GET => write!(f, "GET"),
+            Self::PUT => write!(f, "PUT"),
+            Self::DELETE => write!(f, "DELETE"),
         }
     }
 }
@@ -43,27 +80,35 @@ pub struct Request {
     queries: Vec<(&'static str, String)>, // parameters and queries should not be
     parameters: Vec<(&'static str, String)>, // URL encoded.
     oauth_params: Vec<(&'static str, String)>,
+    form_body: bool,
 }
 
 impl Request {
-    pub fn get<S: ToString>(base_url: S) -> Self {
+    fn new<S: ToString>(method: HttpMethod, base_url: S) -> Self {
         Self {
-            method: HttpMethod::GET,
+            method,
             base_url: base_url.to_string(),
             queries: Vec::new(),
             parameters: Vec::new(),
             oauth_params: Vec::new(),
+            form_body: false,
         }
     }
 
+    pub fn get<S: ToString>(base_url: S) -> Self {
+        Self::new(HttpMethod::GET, base_url)
+    }
+
     pub fn post<S: ToString>(base_url: S) -> Self {
-        Self {
-            method: HttpMethod::POST,
-            base_url: base_url.to_string(),
-            queries: Vec::new(),
-            parameters: Vec::new(),
-            oauth_params: Vec::new(),
-        }
+        Self::new(HttpMethod::POST, base_url)
+    }
+
+    pub fn put<S: ToString>(base_url: S) -> Self {
+        Self::new(HttpMethod::PUT, base_url)
+    }
+
+    pub fn delete<S: ToString>(base_url: S) -> Self {
+        Self::new(HttpMethod::DELETE, base_url)
     }
 
     pub fn query<V: ToString>(&mut self, key: &'static str, value: V) -> &mut Self {
@@ -81,6 +126,13 @@ impl Request {
         self
     }
 
+    /// Sends `parameters` as a form-encoded body. Otherwise they are only
+    /// signed.
+    pub fn form_body(&mut self) -> &mut Self {
+        self.form_body = true;
+        self
+    }
+
     fn get_url(&self) -> String {
         if self.queries.is_empty() {
             self.base_url.clone()
@@ -95,7 +147,15 @@ impl Request {
         }
     }
 
-    pub fn send(&self, tokens: &TokenKeys) -> awc::SendClientRequest {
+    fn get_body(&self) -> String {
+        self.parameters
+            .iter()
+            .map(|(key, value)| format!("{}={}", percent_encode(key), percent_encode(value)))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    pub fn get_oauth_authorization_header(&self, tokens: &TokenKeys) -> String {
         let oauth_nonce = generate_nonce();
         let now: DateTime<Local> = std::time::SystemTime::now().into();
 
@@ -104,19 +164,114 @@ impl Request {
             params.push((percent_encode(key), percent_encode(value)));
         }
         let signature = base64::encode(create_signature(tokens, self, params.clone()));
-        let authorization_header = get_authorization_header(params, &signature);
+        get_authorization_header(params, &signature)
+    }
+
+    pub fn send<'a, A: Into<Authorization<'a>>>(&self, auth: A) -> awc::SendClientRequest {
+        let authorization_header = match auth.into() {
+            Authorization::OAuth(tokens) => Some(self.get_oauth_authorization_header(tokens)),
+            Authorization::Consumer(keys) => {
+                Some(self.get_oauth_authorization_header(&TokenKeys::new(keys.clone())))
+            }
+            Authorization::Basic(keys) => Some(get_basic_authorization_header(keys)),
+            Authorization::Bearer(access_token) => Some(format!("Bearer {}", access_token)),
+            Authorization::Unauthenticated => None,
+        };
 
         let builder = SslConnector::builder(SslMethod::tls()).unwrap();
         let client = Client::builder()
             .connector(Connector::new().ssl(builder.build()).finish())
             .finish();
 
-        match self.method {
+        let mut request = match self.method {
             HttpMethod::POST => client.post(self.get_url()),
             HttpMethod::GET => client.get(self.get_url()),
+            HttpMethod::PUT => client.put(self.get_url()),
+            HttpMethod::DELETE => client.delete(self.get_url()),
+        };
+        if let Some(authorization_header) = authorization_header {
+            request = request.header(AUTHORIZATION, authorization_header);
+        }
+
+        if !self.form_body || self.parameters.is_empty() {
+            request.send()
+        } else {
+            request
+                .content_type("application/x-www-form-urlencoded;charset=UTF-8")
+                .send_body(self.get_body())
+        }
+    }
+}
+
+impl Request {
+    /// Sends the request, turning a non-2xx response into a `TwitterError`.
+    pub async fn send_checked<'a, A: Into<Authorization<'a>>>(&self, auth: A) -> Result<Response> {
+        let mut res = self.send(auth).await?;
+        if res.status().is_success() {
+            return Ok(res);
+        }
+        let mut error = res.json::<TwitterError>().await.unwrap_or(TwitterError {
+            status: 0,
+            rate_limit: None,
+            errors: Vec::new(),
+        });
+        error.status = res.status().as_u16();
+        error.rate_limit = RateLimit::from_headers(res.headers());
+        Err(error.into())
+    }
+
+    pub async fn send_json<'a, T, A>(&self, auth: A) -> Result<T>
+    where
+        T: DeserializeOwned,
+        A: Into<Authorization<'a>>,
+    {
+        Ok(self.send_checked(auth).await?.json().await?)
+    }
+
+    /// Also returns the `x-rate-limit-*` headers of the response.
+    pub async fn send_json_with_rate_limit<'a, T, A>(
+        &self,
+        auth: A,
+    ) -> Result<(T, Option<RateLimit>)>
+    where
+        T: DeserializeOwned,
+        A: Into<Authorization<'a>>,
+    {
+        let mut res = self.send_checked(auth).await?;
+        let rate_limit = RateLimit::from_headers(res.headers());
+        Ok((res.json().await?, rate_limit))
+    }
+}
+
+/// How a request is authorized.
+#[derive(Clone, Copy, Debug)]
+pub enum Authorization<'a> {
+    /// OAuth 1.0a, signed with the consumer keys and the user tokens if any.
+    OAuth(&'a TokenKeys),
+    /// OAuth 1.0a, signed with the consumer keys only.
+    Consumer(&'a KeyPair),
+    /// HTTP Basic with the consumer keys, used to manage bearer tokens.
+    Basic(&'a KeyPair),
+    /// OAuth 2.0 bearer token, either app-only or user context.
+    Bearer(&'a str),
+    /// No `Authorization` header, e.g. for OAuth 2.0 public clients.
+    Unauthenticated,
+}
+
+impl<'a> From<&'a TokenKeys> for Authorization<'a> {
+    fn from(tokens: &'a TokenKeys) -> Self {
+        Self::OAuth(tokens)
+    }
+}
+
+impl<'a> From<Auth<'a>> for Authorization<'a> {
+    fn from(auth: Auth<'a>) -> Self {
+        match auth {
+            Auth::User(tokens) => Self::OAuth(tokens),
+            Auth::Consumer(keys) => Self::Consumer(keys),
+            Auth::AppOnly(token) => Self::Bearer(token.access_token()),
+            Auth::OAuth2User(token) => Self::Bearer(token.access_token()),
         }
-        .header(AUTHORIZATION, authorization_header)
-        .send()
     }
 }
 
@@ -127,6 +282,10 @@ fn create_signature(
     oauth_params: Vec<(String, String)>,
 ) -> [u8; 20] {
     let signature_base_string = get_signature_base_string(request, oauth_params);
+    sign(tokens, &signature_base_string)
+}
+
+pub fn sign(tokens: &TokenKeys, signature_base_string: &str) -> [u8; 20] {
     let signing_key = get_signing_key(tokens);
     hmacsha1::hmac_sha1(signing_key.as_bytes(), signature_base_string.as_bytes())
 }
@@ -172,8 +331,17 @@ fn get_signature_base_string(request: &Request, mut oauth_params: Vec<(String, S
         oauth_params.push((percent_encode(key), percent_encode(value)));
     }
 
-    oauth_params.sort();
-    let param_string = oauth_params
+    signature_base_string(&request.method.to_string(), &request.base_url, oauth_params)
+}
+
+/// `params` must already be percent encoded.
+pub fn signature_base_string(
+    method: &str,
+    base_url: &str,
+    mut params: Vec<(String, String)>,
+) -> String {
+    params.sort();
+    let param_string = params
         .into_iter()
         .map(|(key, value)| format!("{}={}", key, value))
         .collect::<Vec<_>>()
@@ -181,8 +349,8 @@ fn get_signature_base_string(request: &Request, mut oauth_params: Vec<(String, S
 
     format!(
         "{}&{}&{}",
-        percent_encode(&request.method.to_string()),
-        percent_encode(&request.base_url),
+        percent_encode(method),
+        percent_encode(base_url),
         percent_encode(&param_string)
     )
 }
@@ -192,7 +360,7 @@ fn get_signing_key(tokens: &TokenKeys) -> String {
         .oauth_tokens
         .as_ref()
         .map(|key_pair| percent_encode(&key_pair.secret))
-        .unwrap_or_else(|| "".to_string());
+        .unwrap_or_default();
     format!(
         "{}&{}",
         percent_encode(&tokens.consumer_keys.secret),
@@ -200,6 +368,16 @@ fn get_signing_key(tokens: &TokenKeys) -> String {
     )
 }
 
+/// https://developer.twitter.com/en/docs/authentication/oauth-2-0/application-only
+fn get_basic_authorization_header(keys: &KeyPair) -> String {
+    let credentials = format!(
+        "{}:{}",
+        percent_encode(&keys.key),
+        percent_encode(&keys.secret)
+    );
+    format!("Basic {}", base64::encode(credentials))
+}
+
 fn get_authorization_header(mut params: Vec<(String, String)>, signature: &str) -> String {
     params.push((percent_encode("oauth_signature"), percent_encode(signature)));
     params.sort();
@@ -244,10 +422,12 @@ mod tests {
                 "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb".to_string(),
                 "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE".to_string(),
             )),
+            user_id: None,
+            screen_name: None,
         }
     }
 
-    const OAUTH_NONCE: &'static str = "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg";
+    const OAUTH_NONCE: &str = "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg";
     const OAUTH_TIMESTAMP: i64 = 1318622958;
 
     #[test]
@@ -291,6 +471,149 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_header() {
+        let params = get_oauth_params(&get_tokens(), OAUTH_NONCE, OAUTH_TIMESTAMP);
+        let header = get_authorization_header(params, "tnnArxj06cWHq44gCs1OSKk/jLY=");
+        let parsed = parse_oauth_header(&header).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                (
+                    "oauth_consumer_key".to_string(),
+                    "xvz1evFS4wEEPTGEFPHBog".to_string()
+                ),
+                ("oauth_nonce".to_string(), OAUTH_NONCE.to_string()),
+                (
+                    "oauth_signature".to_string(),
+                    "tnnArxj06cWHq44gCs1OSKk/jLY=".to_string()
+                ),
+                (
+                    "oauth_signature_method".to_string(),
+                    "HMAC-SHA1".to_string()
+                ),
+                ("oauth_timestamp".to_string(), "1318622958".to_string()),
+                (
+                    "oauth_token".to_string(),
+                    "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb".to_string()
+                ),
+                ("oauth_version".to_string(), "1.0".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            parse_oauth_header("Basic eHZ6MWV2RlM0d0VFUFRHRUZQSEJvZw=="),
+            None
+        );
+        assert_eq!(parse_oauth_header("OAuth oauth_nonce=unquoted"), None);
+        assert_eq!(
+            parse_oauth_header(r#"oauth oauth_nonce="abc""#),
+            Some(vec![("oauth_nonce".to_string(), "abc".to_string())])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn success_rate_limit() {
+        let srv = actix_web::test::start(|| {
+            actix_web::App::new().route(
+                "/statuses/show.json",
+                actix_web::web::get().to(|| {
+                    std::future::ready(
+                        actix_web::HttpResponse::Ok()
+                            .header("x-rate-limit-limit", "900")
+                            .header("x-rate-limit-remaining", "899")
+                            .header("x-rate-limit-reset", "1318622958")
+                            .content_type("application/json")
+                            .body("{}"),
+                    )
+                }),
+            )
+        });
+        let request = Request::get(srv.url("/statuses/show.json"));
+        let (_, rate_limit) = request
+            .send_json_with_rate_limit::<serde_json::Value, _>(Authorization::Unauthenticated)
+            .await
+            .unwrap();
+        let rate_limit = rate_limit.unwrap();
+        assert_eq!((rate_limit.limit, rate_limit.remaining), (900, 899));
+        assert_eq!(rate_limit.reset.timestamp(), 1318622958);
+    }
+
+    #[test]
+    fn basic_header() {
+        let keys = KeyPair::new(
+            "xvz1evFS4wEEPTGEFPHBog".to_string(),
+            "L8qq9PZyRg6ieKGEKhZolGC0vJWLw8iEJ88DRdyOg".to_string(),
+        );
+        assert_eq!(
+            get_basic_authorization_header(&keys),
+            "Basic eHZ6MWV2RlM0d0VFUFRHRUZQSEJvZzpMOHFxOVBaeVJnNmllS0dFS2hab2xHQzB2SldMdzhpRUo4OERSZHlPZw=="
+        );
+    }
+
+    #[test]
+    fn form_body() {
+        let mut request = Request::post("https://api.twitter.com/oauth2/token");
+        request
+            .parameter("grant_type", "client_credentials")
+            .parameter("status", "Dogs, Cats & Mice");
+        assert_eq!(
+            request.get_body(),
+            "grant_type=client_credentials&status=Dogs%2C%20Cats%20%26%20Mice"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn form_body_is_opt_in() {
+        let srv = actix_web::test::start(|| {
+            actix_web::App::new().route(
+                "/",
+                actix_web::web::post().to(|body: String| async move { body }),
+            )
+        });
+        let mut request = Request::post(srv.url("/"));
+        request.parameter("trim_user", true);
+        let mut res = request.send(Authorization::Unauthenticated).await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "");
+
+        request.form_body();
+        let mut res = request.send(Authorization::Unauthenticated).await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "trim_user=true");
+    }
+
+    const FORBIDDEN: &str = r#"{"errors":[{"code":99,"label":"authenticity_token_error","message":"Unable to verify your credentials"}]}"#;
+
+    #[actix_rt::test]
+    async fn error_status() {
+        let srv = actix_web::test::start(|| {
+            actix_web::App::new().route(
+                "/oauth2/token",
+                actix_web::web::post().to(|| {
+                    std::future::ready(
+                        actix_web::HttpResponse::Forbidden()
+                            .content_type("application/json")
+                            .body(FORBIDDEN),
+                    )
+                }),
+            )
+        });
+        let keys = KeyPair::new("key".to_string(), "secret".to_string());
+        let mut request = Request::post(srv.url("/oauth2/token"));
+        request
+            .parameter("grant_type", "client_credentials")
+            .form_body();
+        let result = request
+            .send_json::<oauth::BearerTokenResponse, _>(Authorization::Basic(&keys))
+            .await;
+        match result {
+            Err(Error::TwitterError(error)) => {
+                assert_eq!(error.status, 403);
+                assert!(error.has_code(99));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
     #[test]
     fn oauth_header() {
         let params = get_oauth_params(&get_tokens(), OAUTH_NONCE, OAUTH_TIMESTAMP);
@@ -0,0 +1,129 @@
+use alpheidae::account_activity::*;
+use chrono::{TimeZone, Utc};
+
+fn activity(json: &str) -> AccountActivity {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn tweet_create_events() {
+    let activity = activity(include_str!(
+        "fixtures/account_activity/tweet_create_events.json"
+    ));
+    assert_eq!(activity.for_user_id, "2244994945");
+    assert_eq!(activity.user_has_blocked, Some(false));
+    match activity.event {
+        AccountActivityEvent::TweetCreateEvents(tweets) => {
+            assert_eq!(tweets.len(), 1);
+            assert_eq!(tweets[0].id, 1050118621198921728);
+            assert_eq!(tweets[0].in_reply_to_user_id, Some(2244994945.into()));
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[test]
+fn favorite_events() {
+    let json = include_str!("fixtures/account_activity/favorite_events.json");
+    let activity = activity(json);
+    match &activity.event {
+        AccountActivityEvent::FavoriteEvents(events) => {
+            assert_eq!(
+                events[0].timestamp_ms,
+                Utc.timestamp_millis_opt(1522082006140).unwrap()
+            );
+            assert_eq!(events[0].user.screen_name, "TwitterDev");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+    let value = serde_json::to_value(&activity).unwrap();
+    assert_eq!(
+        value["favorite_events"][0]["timestamp_ms"],
+        serde_json::json!(1522082006140u64)
+    );
+}
+
+#[test]
+fn follow_events() {
+    let activity = activity(include_str!("fixtures/account_activity/follow_events.json"));
+    match activity.event {
+        AccountActivityEvent::FollowEvents(events) => {
+            assert_eq!(events[0].r#type, "follow");
+            assert_eq!(
+                events[0].created_timestamp,
+                Utc.timestamp_millis_opt(1517588749178).unwrap()
+            );
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[test]
+fn direct_message_events() {
+    let activity = activity(include_str!(
+        "fixtures/account_activity/direct_message_events.json"
+    ));
+    assert_eq!(activity.users.len(), 2);
+    assert_eq!(activity.apps["13090192"].name, "FuriousCamperTestApp1");
+    match activity.event {
+        AccountActivityEvent::DirectMessageEvents(events) => {
+            assert_eq!(
+                events[0].created_timestamp,
+                Utc.timestamp_millis_opt(1516403560557).unwrap()
+            );
+            let message = &events[0].message_create;
+            assert_eq!(message.sender_id, "3001969357");
+            assert_eq!(message.target.recipient_id, "4337869213");
+            assert_eq!(message.message_data.text, "Hello World!");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[test]
+fn tweet_delete_events() {
+    let activity = activity(include_str!(
+        "fixtures/account_activity/tweet_delete_events.json"
+    ));
+    match activity.event {
+        AccountActivityEvent::TweetDeleteEvents(events) => {
+            assert_eq!(events[0].status.id, "1045405559317569537");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[test]
+fn user_event() {
+    let activity = activity(include_str!("fixtures/account_activity/user_event.json"));
+    match activity.event {
+        AccountActivityEvent::UserEvent(UserEvent::Revoke(revoke)) => {
+            assert_eq!(revoke.target.app_id, "13090192");
+            assert_eq!(revoke.source.user_id, "63046977");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_events() {
+    let json = r#"{"for_user_id":"2244994945","tweet_edit_events":[{"id":"1"}]}"#;
+    let unknown = activity(json);
+    match &unknown.event {
+        AccountActivityEvent::Unknown(fields) => {
+            assert_eq!(fields["tweet_edit_events"][0]["id"], "1");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+    assert_eq!(serde_json::to_string(&unknown).unwrap(), json);
+
+    let json = r#"{"for_user_id":"2244994945","user_event":{"suspend":{"user_id":"1"}}}"#;
+    let user_event = activity(json);
+    match &user_event.event {
+        AccountActivityEvent::UserEvent(UserEvent::Unknown(fields)) => {
+            assert_eq!(fields["suspend"]["user_id"], "1");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+    assert_eq!(serde_json::to_string(&user_event).unwrap(), json);
+}
@@ -0,0 +1,119 @@
+use actix_web::client::Client;
+use alpheidae::oauth::{self, CallbackError, CallbackListener, RequestTokenResponse};
+use alpheidae::Error;
+use std::time::Duration;
+
+fn request_token() -> RequestTokenResponse {
+    RequestTokenResponse {
+        oauth_token: "NPcudxy0yU5T3tBzho7iCotZ3cnetKwcTIRlX0iwRl0".to_string(),
+        oauth_token_secret: "veNRnAWe6inFuo8o2u8SLLZLjolYDmDP7SzL0YfYI".to_string(),
+        oauth_callback_confirmed: true,
+    }
+}
+
+#[actix_rt::test]
+async fn receive_callback() {
+    let listener = oauth::callback_server()
+        .success_page("<p>Welcome!</p>".to_string())
+        .bind("127.0.0.1:0")
+        .unwrap();
+    listener.expect(&request_token());
+    let url = format!(
+        "{}?oauth_token=NPcudxy0yU5T3tBzho7iCotZ3cnetKwcTIRlX0iwRl0&oauth_verifier=uw7NjWHT6OJ1MpJOXsHfNxoAhPKpgI8BlYDhxEjIBY",
+        listener.callback_url()
+    );
+
+    let mut res = Client::default().get(url).send().await.unwrap();
+    assert!(res.status().is_success());
+    assert_eq!(res.body().await.unwrap(), "<p>Welcome!</p>");
+
+    let callback = listener.wait(&request_token()).await.unwrap();
+    assert_eq!(
+        callback.oauth_verifier,
+        "uw7NjWHT6OJ1MpJOXsHfNxoAhPKpgI8BlYDhxEjIBY"
+    );
+}
+
+fn callback_url(listener: &CallbackListener, query: &str) -> String {
+    format!("{}?{}", listener.callback_url(), query)
+}
+
+#[actix_rt::test]
+async fn keep_waiting_after_mismatched_token() {
+    let listener = oauth::callback_server().bind("127.0.0.1:0").unwrap();
+    listener.expect(&request_token());
+
+    let forged = callback_url(
+        &listener,
+        "oauth_token=forged&oauth_verifier=uw7NjWHT6OJ1MpJOXsHfNxoAhPKpgI8BlYDhxEjIBY",
+    );
+    let mut res = Client::default().get(forged).send().await.unwrap();
+    assert_eq!(res.status(), 400);
+    let page = res.body().await.unwrap();
+    assert!(String::from_utf8_lossy(&page).contains("Authorization failed"));
+
+    let url = callback_url(
+        &listener,
+        "oauth_token=NPcudxy0yU5T3tBzho7iCotZ3cnetKwcTIRlX0iwRl0&oauth_verifier=uw7NjWHT6OJ1MpJOXsHfNxoAhPKpgI8BlYDhxEjIBY",
+    );
+    let res = Client::default().get(url).send().await.unwrap();
+    assert!(res.status().is_success());
+
+    let callback = listener.wait(&request_token()).await.unwrap();
+    assert_eq!(
+        callback.oauth_verifier,
+        "uw7NjWHT6OJ1MpJOXsHfNxoAhPKpgI8BlYDhxEjIBY"
+    );
+}
+
+#[actix_rt::test]
+async fn keep_waiting_after_missing_parameters() {
+    let listener = oauth::callback_server().bind("127.0.0.1:0").unwrap();
+    listener.expect(&request_token());
+
+    let incomplete = callback_url(
+        &listener,
+        "oauth_token=NPcudxy0yU5T3tBzho7iCotZ3cnetKwcTIRlX0iwRl0",
+    );
+    let res = Client::default().get(incomplete).send().await.unwrap();
+    assert_eq!(res.status(), 400);
+    let forged_denial = callback_url(&listener, "denied=forged");
+    let res = Client::default().get(forged_denial).send().await.unwrap();
+    assert_eq!(res.status(), 400);
+
+    let url = callback_url(
+        &listener,
+        "oauth_token=NPcudxy0yU5T3tBzho7iCotZ3cnetKwcTIRlX0iwRl0&oauth_verifier=uw7NjWHT6OJ1MpJOXsHfNxoAhPKpgI8BlYDhxEjIBY",
+    );
+    Client::default().get(url).send().await.unwrap();
+    assert!(listener.wait(&request_token()).await.is_ok());
+}
+
+#[actix_rt::test]
+async fn denied() {
+    let listener = oauth::callback_server().bind("127.0.0.1:0").unwrap();
+    listener.expect(&request_token());
+    let url = format!(
+        "{}?denied=NPcudxy0yU5T3tBzho7iCotZ3cnetKwcTIRlX0iwRl0",
+        listener.callback_url()
+    );
+    Client::default().get(url).send().await.unwrap();
+
+    match listener.wait(&request_token()).await {
+        Err(Error::CallbackError(CallbackError::Denied)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[actix_rt::test]
+async fn timeout() {
+    let listener = oauth::callback_server()
+        .timeout(Duration::from_millis(100))
+        .bind("127.0.0.1:0")
+        .unwrap();
+
+    match listener.wait(&request_token()).await {
+        Err(Error::CallbackError(CallbackError::Timeout)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,362 @@
+use alpheidae::*;
+use chrono::{TimeZone, Utc};
+
+#[test]
+fn user() {
+    let user: User = serde_json::from_str(include_str!("fixtures/users/twitterdev.json")).unwrap();
+    assert_eq!(user.id, 2244994945);
+    assert_eq!(user.id_str, "2244994945");
+    assert_eq!(user.screen_name, "TwitterDev");
+    assert_eq!(user.location.as_deref(), Some("Internet"));
+    assert_eq!(user.url.as_deref(), Some("https://t.co/FGl7VOULyL"));
+    assert!(user.entities.unwrap().url.is_some());
+    assert!(user.verified);
+    assert!(!user.protected);
+    assert_eq!(user.followers_count, 512292);
+    assert_eq!(user.statuses_count, 3524);
+    assert_eq!(
+        user.created_at,
+        Utc.with_ymd_and_hms(2013, 12, 14, 4, 35, 55).unwrap()
+    );
+    assert_eq!(
+        user.profile_banner_url.as_deref(),
+        Some("https://pbs.twimg.com/profile_banners/2244994945/1594913664")
+    );
+    assert!(user.withheld_in_countries.is_empty());
+    assert_eq!(user.following, Some(false));
+    assert!(user.status.is_none());
+}
+
+#[test]
+fn withheld_user() {
+    let user: User = serde_json::from_str(include_str!("fixtures/users/withheld.json")).unwrap();
+    assert!(user.protected);
+    assert!(user.default_profile_image);
+    assert_eq!(user.withheld_in_countries, vec!["DE", "FR"]);
+    assert_eq!(user.withheld_scope.as_deref(), Some("user"));
+    assert!(user.url.is_none());
+    assert!(user.entities.is_none());
+    assert!(user.profile_banner_url.is_none());
+}
+
+#[test]
+fn entities() {
+    use alpheidae::text::Indices;
+
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/entities.json")).unwrap();
+    assert_eq!(
+        tweet.created_at,
+        Utc.with_ymd_and_hms(2018, 10, 10, 20, 19, 24).unwrap()
+    );
+    let entities = &tweet.entities;
+    assert_eq!(entities.hashtags[0].text, "Rust");
+    assert_eq!(entities.hashtags[0].slice(&tweet.text), Some("#Rust"));
+    assert_eq!(entities.symbols[0].slice(&tweet.text), Some("$TWTR"));
+
+    let mention = &entities.user_mentions[0];
+    assert_eq!(mention.id, 6253282);
+    assert_eq!(mention.slice(&tweet.text), Some("@TwitterAPI"));
+
+    let url = &entities.urls[0];
+    assert_eq!(
+        url.expanded_url.as_deref(),
+        Some("https://blog.twitter.com/developer/en_us.html")
+    );
+    assert_eq!(url.slice(&tweet.text), Some(url.url.as_str()));
+    assert!(entities.polls.is_empty());
+}
+
+#[test]
+fn poll() {
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/poll.json")).unwrap();
+    let poll = &tweet.entities.polls[0];
+    assert_eq!(poll.duration_minutes, 60);
+    assert_eq!(
+        poll.options
+            .iter()
+            .map(|option| option.text.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Standard", "Premium", "Enterprise"]
+    );
+}
+
+#[test]
+fn unknown_media_type() {
+    let media_type: MediaType = serde_json::from_str(r#""model""#).unwrap();
+    assert_eq!(media_type, MediaType::Unknown);
+    let media_type: MediaType = serde_json::from_str(r#""animated_gif""#).unwrap();
+    assert_eq!(media_type, MediaType::AnimatedGif);
+}
+
+#[test]
+fn video() {
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/video.json")).unwrap();
+    assert_eq!(tweet.entities.media[0].r#type, MediaType::Photo);
+
+    let media = &tweet.extended_entities.unwrap().media[0];
+    assert_eq!(media.r#type, MediaType::Video);
+    assert_eq!(media.sizes.large.w, 1280);
+    let video_info = media.video_info.as_ref().unwrap();
+    assert_eq!(video_info.aspect_ratio, [16, 9]);
+    assert_eq!(video_info.duration_millis, Some(30033));
+    assert_eq!(video_info.variants.len(), 4);
+
+    let best = media.best_mp4().unwrap();
+    assert_eq!(best.bitrate, Some(2176000));
+    assert!(best.url.ends_with("/1280x720/c.mp4"));
+
+    let info = media.additional_media_info.as_ref().unwrap();
+    assert_eq!(info.title.as_deref(), Some("Developer Labs"));
+    assert_eq!(info.embeddable, Some(true));
+}
+
+#[test]
+fn photos() {
+    use alpheidae::text::Indices;
+
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/photos.json")).unwrap();
+    let media = tweet.extended_entities.unwrap().media;
+    assert_eq!(media.len(), 2);
+    assert_eq!(media[0].ext_alt_text.as_deref(), Some("A chart"));
+    assert_eq!(media[1].ext_alt_text, None);
+    assert_eq!(
+        media[1].media_url_https,
+        "https://pbs.twimg.com/media/DtHhyl1.jpg"
+    );
+    assert_eq!(media[0].slice(&tweet.text), Some(media[0].url.as_str()));
+    assert!(media[0].best_mp4().is_none());
+}
+
+#[test]
+fn geo() {
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/geo.json")).unwrap();
+    let coordinates = tweet.coordinates.unwrap();
+    assert_eq!(coordinates.r#type, "Point");
+    assert_eq!(coordinates.longitude(), -122.400612831116);
+    assert_eq!(coordinates.latitude(), 37.7821120598956);
+
+    let place = tweet.place.unwrap();
+    assert_eq!(place.full_name, "San Francisco, CA");
+    assert!(place.attributes.is_empty());
+    let bounding_box = &place.bounding_box;
+    assert_eq!(bounding_box.coordinates[0].len(), 4);
+    let [lon, lat] = bounding_box.centroid().unwrap();
+    assert!((lon - -122.4359785).abs() < 1e-9);
+    assert!((lat - 37.7706565).abs() < 1e-9);
+    assert!(bounding_box.contains(coordinates.coordinates));
+    assert!(!bounding_box.contains([-122.27, 37.80]));
+}
+
+#[test]
+fn point_of_interest() {
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/poi.json")).unwrap();
+    assert!(tweet.coordinates.is_none());
+    let place = tweet.place.unwrap();
+    assert_eq!(place.place_type, "poi");
+    assert_eq!(place.attributes["street_address"], "1355 Market St");
+    let [lon, lat] = place.bounding_box.centroid().unwrap();
+    assert!((lon - -122.417).abs() < 1e-9);
+    assert!((lat - 37.7766).abs() < 1e-9);
+    assert!(place.bounding_box.contains([-122.417, 37.7766]));
+}
+
+#[test]
+fn extended_mode() {
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/extended.json")).unwrap();
+    assert_eq!(tweet.text, "");
+    assert!(tweet.complete_text().chars().count() > 140);
+    assert_eq!(Some(tweet.complete_text()), tweet.full_text.as_deref());
+    let range = tweet.display_text_range.unwrap();
+    assert!(text::slice(tweet.complete_text(), range)
+        .unwrap()
+        .starts_with("Extended Tweets are here"));
+}
+
+#[test]
+fn compat_mode() {
+    use alpheidae::text::Indices;
+
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/compat.json")).unwrap();
+    assert!(tweet.truncated);
+    assert_eq!(tweet.text.chars().count(), 140);
+    let extended_tweet = tweet.extended_tweet.as_ref().unwrap();
+    assert_eq!(tweet.complete_text(), extended_tweet.full_text);
+
+    let hashtag = &tweet.complete_entities().hashtags[0];
+    assert_eq!(hashtag.slice(tweet.complete_text()), Some("#extended"));
+    assert!(tweet.entities.hashtags.is_empty());
+}
+
+#[test]
+fn retweet() {
+    use alpheidae::text::Indices;
+
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/retweet.json")).unwrap();
+    assert!(tweet.full_text.as_ref().unwrap().ends_with('\u{2026}'));
+    let retweeted_status = tweet.retweeted_status.as_ref().unwrap();
+    assert_eq!(
+        Some(tweet.complete_text()),
+        retweeted_status.full_text.as_deref()
+    );
+    assert!(tweet.complete_text().ends_with("https://t.co/abcdefghij"));
+
+    let hashtag = &tweet.complete_entities().hashtags[0];
+    assert_eq!(hashtag.slice(tweet.complete_text()), Some("#extended"));
+    assert!(tweet.entities.hashtags.is_empty());
+}
+
+#[test]
+fn matching_rules() {
+    let tweet: Tweet =
+        serde_json::from_str(include_str!("fixtures/tweets/matching_rules.json")).unwrap();
+    let rules = tweet.matching_rules.as_ref().unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].tag.as_deref(), Some("weather"));
+    assert_eq!(rules[0].id, Some(RuleId(1166916266197536768)));
+    assert_eq!(rules[1].tag, None);
+    assert_eq!(tweet.matching_tags().collect::<Vec<_>>(), vec!["weather"]);
+
+    let json = serde_json::to_string(&rules).unwrap();
+    assert_eq!(
+        json,
+        r#"[{"tag":"weather","id":1166916266197536768,"id_str":"1166916266197536768"},{"tag":null,"id":1166916266197536769,"id_str":"1166916266197536769"}]"#
+    );
+    assert_eq!(&serde_json::from_str::<Vec<Rule>>(&json).unwrap(), rules);
+}
+
+#[test]
+fn stream_matching_rules() {
+    let payload: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/stream/filtered_stream.json")).unwrap();
+    let rules: Vec<Rule> = serde_json::from_value(payload["matching_rules"].clone()).unwrap();
+    assert_eq!(rules[0].id, Some(RuleId(1166916266197536768)));
+    assert_eq!(rules[0].id_str, None);
+    assert_eq!(rules[0].tag.as_deref(), Some("weather"));
+    assert_eq!(rules[1].tag, None);
+
+    let payload: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/stream/powertrack_activity.json")).unwrap();
+    let rules: Vec<Rule> =
+        serde_json::from_value(payload["gnip"]["matching_rules"].clone()).unwrap();
+    assert_eq!(rules[0].id, None);
+    assert_eq!(rules[0].extra["value"], "rain OR snow");
+    assert_eq!(
+        serde_json::to_value(&rules).unwrap(),
+        payload["gnip"]["matching_rules"]
+    );
+}
+
+fn assert_round_trip<T>(name: &str, json: &str)
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    let model: T = serde_json::from_str(json).unwrap();
+    let original: serde_json::Value = serde_json::from_str(json).unwrap();
+    let serialized = serde_json::to_value(&model).unwrap();
+    assert_eq!(serialized, original, "{}", name);
+}
+
+#[test]
+fn round_trip() {
+    let tweets = [
+        ("compat", include_str!("fixtures/tweets/compat.json")),
+        ("entities", include_str!("fixtures/tweets/entities.json")),
+        ("extended", include_str!("fixtures/tweets/extended.json")),
+        ("geo", include_str!("fixtures/tweets/geo.json")),
+        (
+            "matching_rules",
+            include_str!("fixtures/tweets/matching_rules.json"),
+        ),
+        ("photos", include_str!("fixtures/tweets/photos.json")),
+        ("poi", include_str!("fixtures/tweets/poi.json")),
+        ("poll", include_str!("fixtures/tweets/poll.json")),
+        ("reply", include_str!("fixtures/tweets/reply.json")),
+        ("retweet", include_str!("fixtures/tweets/retweet.json")),
+        (
+            "sparse_entities",
+            include_str!("fixtures/tweets/sparse_entities.json"),
+        ),
+        ("video", include_str!("fixtures/tweets/video.json")),
+    ];
+    for (name, json) in tweets.iter() {
+        assert_round_trip::<Tweet>(name, json);
+    }
+
+    let users = [
+        ("twitterdev", include_str!("fixtures/users/twitterdev.json")),
+        ("withheld", include_str!("fixtures/users/withheld.json")),
+    ];
+    for (name, json) in users.iter() {
+        assert_round_trip::<User>(name, json);
+    }
+
+    let activities = [
+        (
+            "direct_message_events",
+            include_str!("fixtures/account_activity/direct_message_events.json"),
+        ),
+        (
+            "favorite_events",
+            include_str!("fixtures/account_activity/favorite_events.json"),
+        ),
+        (
+            "follow_events",
+            include_str!("fixtures/account_activity/follow_events.json"),
+        ),
+        (
+            "tweet_create_events",
+            include_str!("fixtures/account_activity/tweet_create_events.json"),
+        ),
+        (
+            "tweet_delete_events",
+            include_str!("fixtures/account_activity/tweet_delete_events.json"),
+        ),
+        (
+            "user_event",
+            include_str!("fixtures/account_activity/user_event.json"),
+        ),
+    ];
+    for (name, json) in activities.iter() {
+        assert_round_trip::<account_activity::AccountActivity>(name, json);
+    }
+}
+
+#[test]
+fn unknown_fields() {
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/geo.json")).unwrap();
+    assert_eq!(tweet.extra["id_str"], "1050118621198921729");
+    assert_eq!(tweet.user.extra["translator_type"], "regular");
+}
+
+#[test]
+fn render() {
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/reply.json")).unwrap();
+    assert_eq!(
+        text::render_text(&tweet),
+        "Q&A on #R\u{fc}st & $TWTR <today>: https://blog.twitter.com/developer/en_us.html?a=1&b=2"
+    );
+    assert_eq!(
+        text::render_html(&tweet),
+        "Q&amp;A on <a href=\"https://twitter.com/hashtag/R%C3%BCst\">#R\u{fc}st</a> &amp; \
+         <a href=\"https://twitter.com/search?q=%24TWTR\">$TWTR</a> &lt;today&gt;: \
+         <a href=\"https://blog.twitter.com/developer/en_us.html?a=1&amp;b=2\">blog.twitter.com/developer/en_u\u{2026}</a>"
+    );
+
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/entities.json")).unwrap();
+    assert_eq!(
+        text::render_html(&tweet),
+        "Caf\u{e9} \u{1f600} with <a href=\"https://twitter.com/TwitterAPI\">@TwitterAPI</a>: \
+         <a href=\"https://twitter.com/hashtag/Rust\">#Rust</a> and \
+         <a href=\"https://twitter.com/search?q=%24TWTR\">$TWTR</a> \
+         <a href=\"https://blog.twitter.com/developer/en_us.html\">blog.twitter.com/developer/en_u\u{2026}</a>"
+    );
+    let tweet: Tweet = serde_json::from_str(include_str!("fixtures/tweets/retweet.json")).unwrap();
+    assert_eq!(
+        text::render_text(&tweet),
+        "Extended Tweets are here: up to 280 characters, and mentions and media no longer \
+         count. This sentence only exists to push the Tweet past the old limit of 140 \
+         characters. #extended"
+    );
+    assert!(text::render_html(&tweet)
+        .ends_with("characters. <a href=\"https://twitter.com/hashtag/extended\">#extended</a>"));
+}
@@ -0,0 +1,183 @@
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use alpheidae::oauth::{self, OAuth2Client, Scope, TokenTypeHint};
+use alpheidae::{Error, OAuth2Token};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+type Form = web::Form<HashMap<String, String>>;
+
+/// A stand-in for `api.twitter.com/2/oauth2`, issuing `access-N`/`refresh-N`.
+#[derive(Default)]
+struct TokenServer {
+    code_challenge: Mutex<Option<String>>,
+    refresh_tokens: Mutex<Vec<String>>,
+    issued: Mutex<u32>,
+}
+
+impl TokenServer {
+    fn issue(&self) -> HttpResponse {
+        let mut issued = self.issued.lock().unwrap();
+        *issued += 1;
+        let refresh_token = format!("refresh-{}", issued);
+        self.refresh_tokens
+            .lock()
+            .unwrap()
+            .push(refresh_token.clone());
+        HttpResponse::Ok().json(json!({
+            "token_type": "bearer",
+            "expires_in": 7200,
+            "access_token": format!("access-{}", issued),
+            "scope": "tweet.read offline.access",
+            "refresh_token": refresh_token,
+        }))
+    }
+}
+
+fn invalid_request(description: &str) -> HttpResponse {
+    HttpResponse::BadRequest().json(json!({
+        "error": "invalid_request",
+        "error_description": description,
+    }))
+}
+
+fn is_client(req: &HttpRequest) -> bool {
+    let expected = format!("Basic {}", base64::encode("client:secret"));
+    req.headers()
+        .get(AUTHORIZATION)
+        .map(|value| value == expected.as_str())
+        .unwrap_or(false)
+}
+
+async fn token(server: web::Data<TokenServer>, req: HttpRequest, form: Form) -> HttpResponse {
+    if !is_client(&req) {
+        return HttpResponse::Unauthorized().json(json!({"error": "unauthorized_client"}));
+    }
+    match form.get("grant_type").map(String::as_str) {
+        Some("authorization_code") => {
+            let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+            let challenge = base64::encode_config(
+                openssl::sha::sha256(verifier.as_bytes()),
+                base64::URL_SAFE_NO_PAD,
+            );
+            let expected = server.code_challenge.lock().unwrap().take();
+            if form.get("code").map(String::as_str) != Some("code")
+                || form.get("redirect_uri").map(String::as_str) != Some("http://127.0.0.1/cb")
+                || expected != Some(challenge)
+            {
+                return invalid_request("Value passed for the authorization code was invalid.");
+            }
+            server.issue()
+        }
+        Some("refresh_token") => {
+            let refresh_token = form.get("refresh_token").cloned().unwrap_or_default();
+            let mut refresh_tokens = server.refresh_tokens.lock().unwrap();
+            match refresh_tokens
+                .iter()
+                .position(|token| *token == refresh_token)
+            {
+                Some(index) => {
+                    refresh_tokens.remove(index);
+                    drop(refresh_tokens);
+                    server.issue()
+                }
+                None => invalid_request("Value passed for the token was invalid."),
+            }
+        }
+        _ => invalid_request("Missing required parameter [grant_type]."),
+    }
+}
+
+async fn revoke(req: HttpRequest, form: Form) -> HttpResponse {
+    if !is_client(&req) {
+        return HttpResponse::Unauthorized().json(json!({"error": "unauthorized_client"}));
+    }
+    let revoked = form.get("token_type_hint").map(String::as_str) == Some("access_token")
+        && form.get("token").map(String::as_str) == Some("access-2");
+    HttpResponse::Ok().json(json!({ "revoked": revoked }))
+}
+
+#[actix_rt::test]
+async fn authorization_code_with_pkce() {
+    let token_server = web::Data::new(TokenServer::default());
+    let data = token_server.clone();
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(data.clone())
+            .route("/2/oauth2/token", web::post().to(token))
+            .route("/2/oauth2/revoke", web::post().to(revoke))
+    })
+    .workers(1)
+    .bind("127.0.0.1:0")
+    .unwrap();
+    let base_url = format!("http://{}", server.addrs()[0]);
+    let server = server.run();
+
+    let client = OAuth2Client::new("client".to_string(), "http://127.0.0.1/cb".to_string())
+        .client_secret("secret".to_string())
+        .authorize_url(format!("{}/i/oauth2/authorize", base_url))
+        .token_url(format!("{}/2/oauth2/token", base_url))
+        .revoke_url(format!("{}/2/oauth2/revoke", base_url));
+
+    let request =
+        oauth::authorize_oauth2(&client, &[Scope::TweetRead, Scope::OfflineAccess]).into_request();
+    assert!(request.url.starts_with(&format!(
+        "{}/i/oauth2/authorize?response_type=code&client_id=client&redirect_uri=http%3A%2F%2F127.0.0.1%2Fcb&scope=tweet.read%20offline.access&state=",
+        base_url
+    )));
+    assert!(request.url.ends_with("&code_challenge_method=S256"));
+
+    // The user approves; the authorization server remembers the challenge.
+    let challenge = request
+        .url
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("code_challenge="))
+        .unwrap();
+    *token_server.code_challenge.lock().unwrap() = Some(challenge.to_string());
+    assert!(request.verify_state(&request.state.clone()));
+
+    let token = request
+        .exchange(&client, "code".to_string())
+        .send()
+        .await
+        .unwrap()
+        .into_token();
+    assert_eq!(token.access_token(), "access-1");
+    assert_eq!(token.refresh_token(), Some("refresh-1"));
+    assert_eq!(token.scopes(), &["tweet.read", "offline.access"]);
+
+    let refreshed = oauth::refresh_oauth2_token(&client, &token)
+        .send()
+        .await
+        .unwrap()
+        .into_token();
+    assert_eq!(refreshed.access_token(), "access-2");
+    assert_eq!(refreshed.refresh_token(), Some("refresh-2"));
+
+    // Refresh tokens rotate: the old one is no longer accepted.
+    match oauth::refresh_oauth2_token(&client, &token).send().await {
+        Err(Error::OAuth2Error(error)) => assert_eq!(error.error, "invalid_request"),
+        other => panic!("unexpected response: {:?}", other),
+    }
+
+    let revoked = oauth::revoke_oauth2_token(&client, refreshed.access_token().to_string())
+        .token_type_hint(TokenTypeHint::AccessToken)
+        .send()
+        .await
+        .unwrap();
+    assert!(revoked);
+
+    // A token restored from storage can still be refreshed.
+    let restored = OAuth2Token::new(refreshed.access_token().to_string())
+        .with_refresh_token(refreshed.refresh_token().unwrap().to_string());
+    let refreshed = oauth::refresh_oauth2_token(&client, &restored)
+        .send()
+        .await
+        .unwrap()
+        .into_token();
+    assert_eq!(refreshed.access_token(), "access-3");
+    assert_eq!(refreshed.refresh_token(), Some("refresh-3"));
+
+    server.stop(false).await;
+}
@@ -0,0 +1,305 @@
+//! The fixtures in `fixtures/twitter_text` are the YAML files of the
+//! twitter-text conformance suite, as published in `twitter_text_conformance`
+//! 0.2.0 under the Apache License 2.0 (see `LICENSE` there), converted to JSON
+//! without changes.
+
+use alpheidae::text::{self, Indices};
+use serde::Deserialize;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Deserialize)]
+struct Case<T> {
+    description: String,
+    text: String,
+    expected: T,
+}
+
+/// `expected` of the weighted sections of `validate.yml`. Some cases leave
+/// fields out.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Weighted {
+    weighted_length: usize,
+    valid: Option<bool>,
+    permillage: Option<usize>,
+    valid_range_start: Option<usize>,
+    valid_range_end: Option<usize>,
+}
+
+fn utf16_len(chars: impl Iterator<Item = char>) -> usize {
+    chars.map(char::len_utf16).sum()
+}
+
+/// Ranges as twitter-text reports them: offsets in UTF-16 code units of the
+/// normalized text, shifted by how much normalization changed its length, with
+/// inclusive ends. Display ranges cover the whole text and aren't checked.
+fn check_weighted(cases: &[Case<Weighted>], config: text::Config) {
+    for case in cases {
+        let parsed = text::parse_tweet_with_config(&case.text, config);
+        let normalized: Vec<char> = case.text.nfc().collect();
+        let shift =
+            utf16_len(case.text.chars()) as isize - utf16_len(normalized.iter().copied()) as isize;
+        let offset = |index: usize| utf16_len(normalized[..index].iter().copied()) as isize;
+        let valid_end = (offset(parsed.valid_range[1]) + shift - 1) as usize;
+        let expected = &case.expected;
+        let fields = [
+            (
+                "weightedLength",
+                Some(expected.weighted_length),
+                parsed.weighted_length,
+            ),
+            (
+                "valid",
+                expected.valid.map(usize::from),
+                usize::from(parsed.valid),
+            ),
+            ("permillage", expected.permillage, parsed.permillage),
+            (
+                "validRangeStart",
+                expected.valid_range_start,
+                parsed.valid_range[0],
+            ),
+            ("validRangeEnd", expected.valid_range_end, valid_end),
+        ];
+        for (name, expected, actual) in fields.iter() {
+            if let Some(expected) = expected {
+                assert_eq!(actual, expected, "{}: {}", case.description, name);
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ValidateConformance {
+    tests: ValidateTests,
+}
+
+/// The weighted sections of `validate.yml`. `tweets` and the other boolean
+/// sections check the 140 character limit of v1 or validators this crate
+/// doesn't have.
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct ValidateTests {
+    WeightedTweetsCounterTest: Vec<Case<Weighted>>,
+    WeightedTweetsWithDiscountedEmojiCounterTest: Vec<Case<Weighted>>,
+    UnicodeDirectionalMarkerCounterTest: Vec<Case<Weighted>>,
+}
+
+#[test]
+fn weighted_length() {
+    let conformance: ValidateConformance =
+        serde_json::from_str(include_str!("fixtures/twitter_text/validate.json")).unwrap();
+    let tests = conformance.tests;
+    check_weighted(&tests.WeightedTweetsCounterTest, text::Config::V2);
+    check_weighted(
+        &tests.WeightedTweetsWithDiscountedEmojiCounterTest,
+        text::Config::V3,
+    );
+    check_weighted(&tests.UnicodeDirectionalMarkerCounterTest, text::Config::V3);
+}
+
+#[test]
+fn valid_range() {
+    let parsed = text::parse_tweet(&format!("{}\u{4f60}\u{4f60}", "a".repeat(279)));
+    assert_eq!(parsed.weighted_length, 283);
+    assert_eq!(parsed.valid_range, [0, 279]);
+}
+
+#[derive(Deserialize)]
+struct ExtractConformance {
+    tests: ExtractTests,
+}
+
+/// Every section of `extract.yml`; a new one fails to deserialize.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExtractTests {
+    mentions: Vec<Case<Vec<String>>>,
+    mentions_with_indices: Vec<Case<Vec<WithIndices>>>,
+    mentions_or_lists_with_indices: Vec<Case<Vec<WithIndices>>>,
+    replies: Vec<Case<Option<String>>>,
+    urls: Vec<Case<Vec<String>>>,
+    urls_with_indices: Vec<Case<Vec<WithIndices>>>,
+    urls_with_directional_markers: Vec<Case<Vec<WithIndices>>>,
+    tco_urls_with_params: Vec<Case<Vec<String>>>,
+    hashtags: Vec<Case<Vec<String>>>,
+    hashtags_from_astral: Vec<Case<Vec<String>>>,
+    hashtags_with_indices: Vec<Case<Vec<WithIndices>>>,
+    cashtags: Vec<Case<Vec<String>>>,
+    cashtags_with_indices: Vec<Case<Vec<WithIndices>>>,
+}
+
+/// An entity of `extract.yml`, keyed by `screen_name`, `hashtag`, `cashtag`
+/// or `url`.
+#[derive(Deserialize, Debug, PartialEq)]
+struct WithIndices {
+    #[serde(
+        alias = "screen_name",
+        alias = "hashtag",
+        alias = "cashtag",
+        alias = "url"
+    )]
+    text: String,
+    #[serde(default)]
+    list_slug: Option<String>,
+    indices: [usize; 2],
+}
+
+fn with_indices<E: Indices>(entities: &[E], text: impl Fn(&E) -> String) -> Vec<WithIndices> {
+    entities
+        .iter()
+        .map(|entity| WithIndices {
+            text: text(entity),
+            list_slug: None,
+            indices: entity.indices(),
+        })
+        .collect()
+}
+
+/// Runs `cases`, collecting failures to report them all at once.
+fn check<T, F>(section: &str, cases: &[Case<T>], extract: F, failures: &mut Vec<String>)
+where
+    T: std::fmt::Debug + PartialEq,
+    F: Fn(&str) -> T,
+{
+    for case in cases {
+        let actual = extract(&case.text);
+        if actual != case.expected {
+            failures.push(format!(
+                "{}: {}\n  expected: {:?}\n    actual: {:?}",
+                section, case.description, case.expected, actual
+            ));
+        }
+    }
+}
+
+fn screen_names(text: &str) -> Vec<text::Mention> {
+    text::extract_mentions(text)
+        .into_iter()
+        .filter(|mention| mention.list_slug.is_none())
+        .collect()
+}
+
+fn url_strings(text: &str) -> Vec<String> {
+    text::extract_urls(text)
+        .into_iter()
+        .map(|url| url.url)
+        .collect()
+}
+
+fn urls_with_indices(text: &str) -> Vec<WithIndices> {
+    with_indices(&text::extract_urls(text), |url| url.url.clone())
+}
+
+fn hashtag_strings(text: &str) -> Vec<String> {
+    text::extract_hashtags(text)
+        .into_iter()
+        .map(|hashtag| hashtag.text)
+        .collect()
+}
+
+#[test]
+fn extract() {
+    let conformance: ExtractConformance =
+        serde_json::from_str(include_str!("fixtures/twitter_text/extract.json")).unwrap();
+    let tests = conformance.tests;
+    let mut failures = Vec::new();
+
+    check(
+        "mentions",
+        &tests.mentions,
+        |text| {
+            screen_names(text)
+                .into_iter()
+                .map(|mention| mention.screen_name)
+                .collect()
+        },
+        &mut failures,
+    );
+    check(
+        "mentions_with_indices",
+        &tests.mentions_with_indices,
+        |text| with_indices(&screen_names(text), |mention| mention.screen_name.clone()),
+        &mut failures,
+    );
+    check(
+        "mentions_or_lists_with_indices",
+        &tests.mentions_or_lists_with_indices,
+        |text| {
+            text::extract_mentions(text)
+                .into_iter()
+                .map(|mention| WithIndices {
+                    text: mention.screen_name,
+                    list_slug: Some(
+                        mention
+                            .list_slug
+                            .map_or_else(String::new, |slug| format!("/{}", slug)),
+                    ),
+                    indices: mention.indices,
+                })
+                .collect()
+        },
+        &mut failures,
+    );
+    check(
+        "replies",
+        &tests.replies,
+        text::extract_reply_screen_name,
+        &mut failures,
+    );
+    check("urls", &tests.urls, url_strings, &mut failures);
+    check(
+        "urls_with_indices",
+        &tests.urls_with_indices,
+        urls_with_indices,
+        &mut failures,
+    );
+    check(
+        "urls_with_directional_markers",
+        &tests.urls_with_directional_markers,
+        urls_with_indices,
+        &mut failures,
+    );
+    check(
+        "tco_urls_with_params",
+        &tests.tco_urls_with_params,
+        url_strings,
+        &mut failures,
+    );
+    check("hashtags", &tests.hashtags, hashtag_strings, &mut failures);
+    check(
+        "hashtags_from_astral",
+        &tests.hashtags_from_astral,
+        hashtag_strings,
+        &mut failures,
+    );
+    check(
+        "hashtags_with_indices",
+        &tests.hashtags_with_indices,
+        |text| {
+            with_indices(&text::extract_hashtags(text), |hashtag| {
+                hashtag.text.clone()
+            })
+        },
+        &mut failures,
+    );
+    check(
+        "cashtags",
+        &tests.cashtags,
+        |text| {
+            text::extract_cashtags(text)
+                .into_iter()
+                .map(|symbol| symbol.text)
+                .collect()
+        },
+        &mut failures,
+    );
+    check(
+        "cashtags_with_indices",
+        &tests.cashtags_with_indices,
+        |text| with_indices(&text::extract_cashtags(text), |symbol| symbol.text.clone()),
+        &mut failures,
+    );
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
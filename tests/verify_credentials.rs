@@ -0,0 +1,25 @@
+use alpheidae::*;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Config {
+    consumer_key: String,
+    consumer_secret: String,
+    access_token: String,
+    access_token_secret: String,
+}
+
+#[actix_rt::test]
+async fn verify_credentials() {
+    let config: Config = envy::from_env().unwrap();
+    let consumer_keys = KeyPair::new(config.consumer_key, config.consumer_secret);
+    let oauth_tokens = KeyPair::new(config.access_token, config.access_token_secret);
+    let tokens = TokenKeys::new(consumer_keys).oauth_tokens(oauth_tokens);
+
+    let (_user, access_level) = account::verify_credentials(&tokens)
+        .skip_status(true)
+        .send_with_access_level()
+        .await
+        .unwrap();
+    assert!(access_level.is_some());
+}